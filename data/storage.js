@@ -0,0 +1,36 @@
+"use strict";
+
+miniquad_add_plugin({
+  name: "triplanetary_storage",
+  version: 1,
+  register_plugin: function (importObject) {
+    importObject.env.triplanetary_download = function (name_ptr, name_len, data_ptr, data_len) {
+      const decoder = new TextDecoder();
+      const name = decoder.decode(new Uint8Array(wasm_memory.buffer, name_ptr, name_len));
+      const data = new Uint8Array(wasm_memory.buffer, data_ptr, data_len).slice();
+      const url = URL.createObjectURL(new Blob([data], { type: "text/plain" }));
+      const link = document.createElement("a");
+      link.href = url;
+      link.download = name;
+      link.click();
+      URL.revokeObjectURL(url);
+    };
+    importObject.env.triplanetary_request_upload = function () {
+      const input = document.createElement("input");
+      input.type = "file";
+      input.onchange = function () {
+        if (input.files.length === 0) {
+          return;
+        }
+        input.files[0].arrayBuffer().then(function (contents) {
+          const bytes = new Uint8Array(contents);
+          const ptr = wasm_exports.triplanetary_upload_alloc(bytes.length);
+          new Uint8Array(wasm_memory.buffer, ptr, bytes.length).set(bytes);
+          wasm_exports.triplanetary_upload_ready();
+          canvas.focus();
+        });
+      };
+      input.click();
+    };
+  },
+});
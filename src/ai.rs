@@ -0,0 +1,138 @@
+use crate::hex::Hex;
+use crate::save::{
+  Persist,
+  SaveError,
+  SaveReader,
+  SaveWriter,
+};
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Difficulty {
+  Easy,
+  Normal,
+  Hard,
+}
+
+impl Difficulty {
+  pub fn weights(self) -> AiWeights {
+    match self {
+      Difficulty::Easy => AiWeights {
+        attack: 1.,
+        pursue: 0.6,
+        refuel: 0.5,
+        evade: 0.5,
+        objective: 0.4,
+        min_odds: 3,
+        launch_range: 0,
+      },
+      Difficulty::Normal => AiWeights {
+        attack: 1.,
+        pursue: 0.8,
+        refuel: 1.,
+        evade: 1.,
+        objective: 0.5,
+        min_odds: 2,
+        launch_range: 4,
+      },
+      Difficulty::Hard => AiWeights {
+        attack: 1.2,
+        pursue: 1.,
+        refuel: 1.2,
+        evade: 1.5,
+        objective: 0.5,
+        min_odds: 1,
+        launch_range: 6,
+      },
+    }
+  }
+}
+
+// How much an AI ship wants each kind of option, and the limits it fights by.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct AiWeights {
+  pub attack: f32,
+  pub pursue: f32,
+  pub refuel: f32,
+  pub evade: f32,
+  pub objective: f32,
+  // the worst odds column it will still fire at
+  pub min_odds: usize,
+  // how close a target has to be before it launches torpedoes, 0 for never
+  pub launch_range: i32,
+}
+
+impl Persist for AiWeights {
+  fn write(&self, w: &mut SaveWriter) {
+    w.write(&self.attack);
+    w.write(&self.pursue);
+    w.write(&self.refuel);
+    w.write(&self.evade);
+    w.write(&self.objective);
+    w.write(&self.min_odds);
+    w.write(&self.launch_range);
+  }
+
+  fn read(r: &mut SaveReader) -> Result<Self, SaveError> {
+    Ok(AiWeights {
+      attack: r.read()?,
+      pursue: r.read()?,
+      refuel: r.read()?,
+      evade: r.read()?,
+      objective: r.read()?,
+      min_odds: r.read()?,
+      launch_range: r.read()?,
+    })
+  }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Ai {
+  pub weights: AiWeights,
+  // where the scenario wants this ship to go, if anywhere
+  pub objective: Option<Hex>,
+}
+
+impl Ai {
+  pub fn new(difficulty: Difficulty) -> Self {
+    Ai {
+      weights: difficulty.weights(),
+      objective: None,
+    }
+  }
+}
+
+impl Persist for Ai {
+  fn write(&self, w: &mut SaveWriter) {
+    w.write(&self.weights);
+    w.write(&self.objective);
+  }
+
+  fn read(r: &mut SaveReader) -> Result<Self, SaveError> {
+    Ok(Ai {
+      weights: r.read()?,
+      objective: r.read()?,
+    })
+  }
+}
+
+// Budget for playing burns forward before picking one: how many turns each
+// rollout looks ahead and how many rollouts one decision may spend.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Lookahead {
+  pub depth: i32,
+  pub rollouts: i32,
+}
+
+impl Persist for Lookahead {
+  fn write(&self, w: &mut SaveWriter) {
+    w.write(&self.depth);
+    w.write(&self.rollouts);
+  }
+
+  fn read(r: &mut SaveReader) -> Result<Self, SaveError> {
+    Ok(Lookahead {
+      depth: r.read()?,
+      rollouts: r.read()?,
+    })
+  }
+}
@@ -1,6 +1,34 @@
-use macroquad::color_u8;
+use crate::save::{
+  Persist,
+  SaveError,
+  SaveReader,
+  SaveWriter,
+};
 
-pub use macroquad::color::Color;
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Color {
+  pub r: f32,
+  pub g: f32,
+  pub b: f32,
+  pub a: f32,
+}
+
+impl Color {
+  pub const fn new(r: f32, g: f32, b: f32, a: f32) -> Color {
+    Color { r, g, b, a }
+  }
+}
+
+macro_rules! color_u8 {
+  ($r:expr, $g:expr, $b:expr, $a:expr) => {
+    Color::new(
+      $r as f32 / 255.,
+      $g as f32 / 255.,
+      $b as f32 / 255.,
+      $a as f32 / 255.,
+    )
+  };
+}
 
 pub const WHITE: Color = color_u8!(238, 238, 236, 255);
 pub const GRAY: Color = color_u8!(128, 128, 128, 255);
@@ -18,3 +46,16 @@ pub const DARK_BLUE: Color = color_u8!(80, 100, 180, 255);
 pub const DARK_CYAN: Color = color_u8!(114, 159, 207, 255);
 pub const DARK_MAGENTA: Color = color_u8!(173, 127, 168, 255);
 pub const DARK_YELLOW: Color = color_u8!(180, 170, 60, 255);
+
+impl Persist for Color {
+  fn write(&self, w: &mut SaveWriter) {
+    w.write(&self.r);
+    w.write(&self.g);
+    w.write(&self.b);
+    w.write(&self.a);
+  }
+
+  fn read(r: &mut SaveReader) -> Result<Self, SaveError> {
+    Ok(Color::new(r.read()?, r.read()?, r.read()?, r.read()?))
+  }
+}
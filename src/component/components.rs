@@ -1,14 +1,21 @@
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 use crate::entity::EntityId;
+use crate::save::{
+  Persist,
+  SaveError,
+  SaveReader,
+  SaveWriter,
+};
 
+#[derive(Clone)]
 pub struct Components<T> {
-  by_id: HashMap<EntityId, T>,
+  by_id: BTreeMap<EntityId, T>,
 }
 
 impl<T> Default for Components<T> {
   fn default() -> Self {
     Self {
-      by_id: HashMap::new(),
+      by_id: BTreeMap::new(),
     }
   }
 }
@@ -33,5 +40,40 @@ impl<T> Components<T>{
   pub fn del(&mut self, entity_id: EntityId) {
     self.by_id.remove(&entity_id);
   }
+
+  pub fn iter(&self) -> impl Iterator<Item = (EntityId, &T)> {
+    self.by_id.iter().map(|(k, v)| (*k, v))
+  }
+
+  pub fn filtered(&self, keep: impl Fn(EntityId) -> bool) -> Self where T: Clone {
+    Self {
+      by_id: self.by_id.iter()
+        .filter(|(k, _)| keep(**k))
+        .map(|(k, v)| (*k, v.clone()))
+        .collect(),
+    }
+  }
+}
+
+impl<T: Persist> Persist for Components<T> {
+  fn write(&self, w: &mut SaveWriter) {
+    w.write(&self.by_id.len());
+    w.newline();
+    for (entity_id, component) in self.by_id.iter() {
+      w.write(entity_id);
+      w.write(component);
+      w.newline();
+    }
+  }
+
+  fn read(r: &mut SaveReader) -> Result<Self, SaveError> {
+    let length: usize = r.read()?;
+    let mut components = Self::default();
+    for _ in 0..length {
+      let entity_id = r.read()?;
+      components.set(entity_id, r.read()?);
+    }
+    Ok(components)
+  }
 }
 
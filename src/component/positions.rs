@@ -1,19 +1,25 @@
 use std::collections::{
+  BTreeSet,
   HashMap,
-  HashSet,
 };
 use crate::entity::EntityId;
 use crate::hex::Hex;
+use crate::save::{
+  Persist,
+  SaveError,
+  SaveReader,
+  SaveWriter,
+};
 use super::components::Components;
 
-#[derive(Default)]
+#[derive(Clone, Default)]
 pub struct Positions {
   components: Components<Hex>,
-  by_hex: HashMap<Hex, HashSet<EntityId>>,
+  by_hex: HashMap<Hex, BTreeSet<EntityId>>,
 }
 
 impl Positions {
-  pub fn at(&self, hex: Hex) -> Option<&HashSet<EntityId>> {
+  pub fn at(&self, hex: Hex) -> Option<&BTreeSet<EntityId>> {
     self.by_hex.get(&hex)
   }
 
@@ -33,7 +39,7 @@ impl Positions {
     self.by_hex.entry(hex)
       .and_modify(|v| { v.insert(entity_id); })
       .or_insert_with(|| {
-        let mut hs = HashSet::with_capacity(1);
+        let mut hs = BTreeSet::new();
         hs.insert(entity_id);
         hs
       });
@@ -45,5 +51,30 @@ impl Positions {
     }
     self.components.del(entity_id);
   }
+
+  pub fn filtered(&self, keep: impl Fn(EntityId) -> bool) -> Self {
+    let mut positions = Self::default();
+    for (entity_id, hex) in self.components.iter() {
+      if keep(entity_id) {
+        positions.set(entity_id, *hex);
+      }
+    }
+    positions
+  }
 }
 
+
+impl Persist for Positions {
+  fn write(&self, w: &mut SaveWriter) {
+    w.write(&self.components);
+  }
+
+  fn read(r: &mut SaveReader) -> Result<Self, SaveError> {
+    let components: Components<Hex> = r.read()?;
+    let mut positions = Self::default();
+    for (entity_id, hex) in components.iter() {
+      positions.set(entity_id, *hex);
+    }
+    Ok(positions)
+  }
+}
@@ -1,23 +1,41 @@
 use std::iter;
-use macroquad::time::get_time;
 
 enum Span {
   Started(f64),
   Ended(f64),
 }
 
-#[derive(Default)]
 pub struct Counters {
+  clock: fn() -> f64,
   spans: Vec<(usize, String, Span)>,
   frame_start: f64,
   open_spans: usize,
   max_span_name_size: usize,
 }
 
+impl Default for Counters {
+  fn default() -> Self {
+    fn no_clock() -> f64 {
+      0.
+    }
+    Self::new(no_clock)
+  }
+}
+
 impl Counters {
+  pub fn new(clock: fn() -> f64) -> Self {
+    Self {
+      clock,
+      spans: vec![],
+      frame_start: clock(),
+      open_spans: 0,
+      max_span_name_size: 0,
+    }
+  }
+
   pub fn begin(&mut self, name: &str) -> usize {
     let index = self.spans.len();
-    self.spans.push((self.open_spans, name.to_string(), Span::Started(get_time())));
+    self.spans.push((self.open_spans, name.to_string(), Span::Started((self.clock)())));
     self.open_spans += 1;
     self.max_span_name_size = self.max_span_name_size.max(name.len());
     index
@@ -29,7 +47,7 @@ impl Counters {
     };
     let elapsed = match span {
       Span::Started(start) => {
-        get_time() - *start
+        (self.clock)() - *start
       },
       Span::Ended(_) => panic!("cannot end a span twice"),
     };
@@ -39,7 +57,7 @@ impl Counters {
 
   pub fn reset(&mut self) {
     self.spans.clear();
-    self.frame_start = get_time();
+    self.frame_start = (self.clock)();
     self.open_spans = 0;
     self.max_span_name_size = 0;
   }
@@ -56,7 +74,7 @@ impl Counters {
         .collect::<String>();
   		output.push_str(&format!("{indent}{name:width$} {elapsed}ms\n"));
 		}
-  	let total_elapsed = get_time() - self.frame_start;
+  	let total_elapsed = (self.clock)() - self.frame_start;
     let total_elapsed = (total_elapsed * 1000.).floor() as i64;
 		output.push_str(&format!("TOTAL: {total_elapsed}ms\n"));
 		output
@@ -0,0 +1,67 @@
+use std::collections::BTreeMap;
+use crate::hex::Hex;
+use crate::save::{
+  Persist,
+  SaveError,
+  SaveReader,
+  SaveWriter,
+};
+use crate::world::VisibleObject;
+
+// Changes to the procedural world are grouped by chunks this many hexes
+// across.
+pub const CHUNK_SIZE: i32 = 16;
+
+pub fn chunk_of(position: Hex) -> (i32, i32) {
+  (position.q.div_euclid(CHUNK_SIZE), position.r.div_euclid(CHUNK_SIZE))
+}
+
+// What has changed in one chunk since it was first generated. Each entry
+// replaces whatever generation would put in the hex: nothing when its object
+// was destroyed or moved away, or the object that was left there.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ChunkDelta {
+  pub hexes: BTreeMap<(i32, i32), Option<VisibleObject>>,
+}
+
+impl Persist for ChunkDelta {
+  fn write(&self, w: &mut SaveWriter) {
+    w.write(&self.hexes);
+  }
+
+  fn read(r: &mut SaveReader) -> Result<Self, SaveError> {
+    Ok(ChunkDelta {
+      hexes: r.read()?,
+    })
+  }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct WorldDeltas {
+  pub chunks: BTreeMap<(i32, i32), ChunkDelta>,
+}
+
+impl WorldDeltas {
+  // `None` when the hex is as generated, otherwise what it holds now.
+  pub fn get(&self, position: Hex) -> Option<Option<VisibleObject>> {
+    let chunk = self.chunks.get(&chunk_of(position))?;
+    chunk.hexes.get(&(position.q, position.r)).copied()
+  }
+
+  pub fn set(&mut self, position: Hex, object: Option<VisibleObject>) {
+    let chunk = self.chunks.entry(chunk_of(position)).or_default();
+    chunk.hexes.insert((position.q, position.r), object);
+  }
+}
+
+impl Persist for WorldDeltas {
+  fn write(&self, w: &mut SaveWriter) {
+    w.write(&self.chunks);
+  }
+
+  fn read(r: &mut SaveReader) -> Result<Self, SaveError> {
+    Ok(WorldDeltas {
+      chunks: r.read()?,
+    })
+  }
+}
@@ -2,8 +2,14 @@ use core::ops::{
   Index,
   IndexMut,
 };
+use crate::save::{
+  Persist,
+  SaveError,
+  SaveReader,
+  SaveWriter,
+};
 
-#[derive(Copy, Clone, Debug, Hash, PartialEq)]
+#[derive(Copy, Clone, Debug, Hash, PartialEq, PartialOrd, Ord)]
 pub struct EntityId {
   index: u16,
 }
@@ -24,6 +30,16 @@ impl std::fmt::Display for EntityId {
 
 impl Eq for EntityId {}
 
+impl Persist for EntityId {
+  fn write(&self, w: &mut SaveWriter) {
+    w.write(&self.index);
+  }
+
+  fn read(r: &mut SaveReader) -> Result<Self, SaveError> {
+    Ok(new_entity_id(r.read()?))
+  }
+}
+
 impl<T> Index<EntityId> for [T]
 where
   [T]: Index<usize>,
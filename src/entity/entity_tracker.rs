@@ -6,11 +6,17 @@ use super::entity_id::{
   new_entity_id,
   index_of_entity_id,
 };
+use crate::save::{
+  Persist,
+  SaveError,
+  SaveReader,
+  SaveWriter,
+};
 
 pub const ENTITY_MAX: usize = 8192;
 pub const REUSE_SIZE: usize = 100;
 
-#[derive(Default)]
+#[derive(Clone, Default)]
 pub struct EntityTracker {
   next_index: u16,
   unused_indices: VecDeque<u16>,
@@ -39,3 +45,17 @@ impl EntityTracker {
     (0..count).map(new_entity_id as fn(u16) -> EntityId)
   }
 }
+
+impl Persist for EntityTracker {
+  fn write(&self, w: &mut SaveWriter) {
+    w.write(&self.next_index);
+    w.write(&self.unused_indices);
+  }
+
+  fn read(r: &mut SaveReader) -> Result<Self, SaveError> {
+    Ok(Self {
+      next_index: r.read()?,
+      unused_indices: r.read()?,
+    })
+  }
+}
@@ -0,0 +1,150 @@
+use std::collections::{
+  BTreeMap,
+  BTreeSet,
+  HashMap,
+  HashSet,
+};
+use crate::color::Color;
+use crate::entity::EntityId;
+use crate::hex::Hex;
+use crate::save::{
+  Persist,
+  SaveError,
+  SaveReader,
+  SaveWriter,
+};
+use crate::world::VisibleObject;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FactionId(pub u8);
+
+impl Persist for FactionId {
+  fn write(&self, w: &mut SaveWriter) {
+    w.write(&self.0);
+  }
+
+  fn read(r: &mut SaveReader) -> Result<Self, SaveError> {
+    Ok(FactionId(r.read()?))
+  }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Relation {
+  Friend,
+  Foe,
+}
+
+// Something a faction's sensors have picked up. Until it has been identified
+// all the faction knows is that something is there.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Contact {
+  pub position: Hex,
+  pub identified: bool,
+}
+
+impl Persist for Contact {
+  fn write(&self, w: &mut SaveWriter) {
+    w.write(&self.position);
+    w.write(&self.identified);
+  }
+
+  fn read(r: &mut SaveReader) -> Result<Self, SaveError> {
+    Ok(Contact {
+      position: r.read()?,
+      identified: r.read()?,
+    })
+  }
+}
+
+// What a faction saw in a hex the last time it was in sight.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Sighting {
+  pub turn: i32,
+  pub objects: Vec<VisibleObject>,
+}
+
+impl Persist for Sighting {
+  fn write(&self, w: &mut SaveWriter) {
+    w.write(&self.turn);
+    w.write(&self.objects);
+  }
+
+  fn read(r: &mut SaveReader) -> Result<Self, SaveError> {
+    Ok(Sighting {
+      turn: r.read()?,
+      objects: r.read()?,
+    })
+  }
+}
+
+// A side in the game and everything it knows about the board.
+#[derive(Clone, Debug)]
+pub struct Faction {
+  pub name: String,
+  pub color: Color,
+  pub allies: BTreeSet<FactionId>,
+  // hexes in sight of any of the faction's ships or bases right now
+  pub visible: HashSet<Hex>,
+  // every hex the faction has ever had in sight, and what was last seen there
+  pub explored: HashMap<Hex, Sighting>,
+  // ships and ordnance of other factions its sensors currently detect
+  pub contacts: BTreeMap<EntityId, Contact>,
+}
+
+impl Faction {
+  pub fn new(name: &str, color: Color) -> Self {
+    Faction {
+      name: name.to_string(),
+      color,
+      allies: BTreeSet::new(),
+      visible: HashSet::new(),
+      explored: HashMap::new(),
+      contacts: BTreeMap::new(),
+    }
+  }
+
+  // The faction with its allies and contacts but none of its map, which the
+  // vision system fills in again from what is in sight.
+  pub fn without_memory(&self) -> Self {
+    Faction {
+      name: self.name.clone(),
+      color: self.color,
+      allies: self.allies.clone(),
+      visible: HashSet::new(),
+      explored: HashMap::new(),
+      contacts: self.contacts.clone(),
+    }
+  }
+}
+
+// What is in sight is worked out again by the vision system after loading,
+// so only the explored hexes and what was last seen in them are written, in
+// a fixed order so that the same world always saves the same way. Contacts
+// are kept since whether they have been identified depends on earlier turns.
+impl Persist for Faction {
+  fn write(&self, w: &mut SaveWriter) {
+    w.write(&self.name);
+    w.write(&self.color);
+    w.write(&self.allies.iter().copied().collect::<Vec<_>>());
+    let mut explored: Vec<_> = self.explored.iter().map(|(k, v)| ((k.q, k.r), v.clone())).collect();
+    explored.sort_by_key(|x| x.0);
+    w.write(&explored);
+    w.write(&self.contacts);
+  }
+
+  fn read(r: &mut SaveReader) -> Result<Self, SaveError> {
+    let name = r.read()?;
+    let color = r.read()?;
+    let allies: Vec<FactionId> = r.read()?;
+    let explored: Vec<((i32, i32), Sighting)> = r.read()?;
+    let contacts = r.read()?;
+    Ok(Faction {
+      name,
+      color,
+      allies: allies.into_iter().collect(),
+      visible: HashSet::new(),
+      explored: explored.into_iter().map(|((q, r), x)| (Hex::new(q, r), x)).collect(),
+      contacts,
+    })
+  }
+}
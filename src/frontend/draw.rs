@@ -0,0 +1,17 @@
+use macroquad::prelude::{
+  IVec2,
+  draw_line as mq_draw_line,
+};
+use triplanetary::Color;
+use super::anchor::MIDDLE_CENTER;
+
+pub fn mq_color(color: Color) -> macroquad::color::Color {
+  macroquad::color::Color::new(color.r, color.g, color.b, color.a)
+}
+
+pub fn draw_line(color: Color, width: i32, a: IVec2 , b: IVec2) {
+  let offset = MIDDLE_CENTER.window_offset();
+  let a = (a + offset).as_vec2();
+  let b = (b + offset).as_vec2();
+  mq_draw_line(a.x, a.y, b.x, b.y, width as f32, mq_color(color));
+}
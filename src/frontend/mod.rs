@@ -0,0 +1,12 @@
+
+mod anchor;
+mod draw;
+mod resources;
+mod sprite;
+mod storage;
+mod view;
+
+pub use anchor::*;
+pub use draw::*;
+pub use resources::*;
+pub use storage::*;
@@ -3,11 +3,11 @@ use macroquad::prelude::{
   load_texture,
   build_textures_atlas,
 };
-use crate::anchor::MIDDLE_BOTTOM;
-use crate::sprite::{
+use super::anchor::MIDDLE_BOTTOM;
+use super::sprite::{
   Sprite,
 };
-use crate::hex::{
+use super::view::{
   HexView,
   HexOrientation,
 };
@@ -21,6 +21,11 @@ pub struct Resources {
   pub hex_filled: Sprite,
   pub ship: Sprite,
   pub asteroid: Sprite,
+  pub planet: Sprite,
+  pub base: Sprite,
+  pub torpedo: Sprite,
+  pub mine: Sprite,
+  pub nuke: Sprite,
   pub pip_open: Sprite,
   pub pip_closed: Sprite,
   pub gravity_arrow_0: Sprite,
@@ -29,6 +34,12 @@ pub struct Resources {
   pub gravity_arrow_3: Sprite,
   pub gravity_arrow_4: Sprite,
   pub gravity_arrow_5: Sprite,
+  pub weak_gravity_arrow_0: Sprite,
+  pub weak_gravity_arrow_1: Sprite,
+  pub weak_gravity_arrow_2: Sprite,
+  pub weak_gravity_arrow_3: Sprite,
+  pub weak_gravity_arrow_4: Sprite,
+  pub weak_gravity_arrow_5: Sprite,
 }
 
 impl Resources {
@@ -38,6 +49,11 @@ impl Resources {
       hex_filled: create_hex_sprite("hex_filled.png", 0, 0).await,
       ship: create_hex_sprite("ship.png", 0, 0).await,
       asteroid: create_hex_sprite("asteroid.png", 0, 0).await,
+      planet: create_hex_sprite("planet.png", 0, 0).await,
+      base: create_hex_sprite("base.png", 0, 0).await,
+      torpedo: create_hex_sprite("torpedo.png", 0, 0).await,
+      mine: create_hex_sprite("mine.png", 0, 0).await,
+      nuke: create_hex_sprite("nuke.png", 0, 0).await,
       pip_open: create_hex_sprite("pip_open.png", 0, 0).await,
       pip_closed: create_hex_sprite("pip_closed.png", 0, 0).await,
       gravity_arrow_0: create_hex_sprite("gravity_arrow_0.png", 0, 0).await,
@@ -46,6 +62,12 @@ impl Resources {
       gravity_arrow_3: create_hex_sprite("gravity_arrow_3.png", 0, 0).await,
       gravity_arrow_4: create_hex_sprite("gravity_arrow_4.png", 0, 0).await,
       gravity_arrow_5: create_hex_sprite("gravity_arrow_5.png", 0, 0).await,
+      weak_gravity_arrow_0: create_hex_sprite("weak_gravity_arrow_0.png", 0, 0).await,
+      weak_gravity_arrow_1: create_hex_sprite("weak_gravity_arrow_1.png", 0, 0).await,
+      weak_gravity_arrow_2: create_hex_sprite("weak_gravity_arrow_2.png", 0, 0).await,
+      weak_gravity_arrow_3: create_hex_sprite("weak_gravity_arrow_3.png", 0, 0).await,
+      weak_gravity_arrow_4: create_hex_sprite("weak_gravity_arrow_4.png", 0, 0).await,
+      weak_gravity_arrow_5: create_hex_sprite("weak_gravity_arrow_5.png", 0, 0).await,
     };
     build_textures_atlas();
     r
@@ -8,11 +8,12 @@ use macroquad::prelude::{
   draw_texture_ex,
   DrawTextureParams,
 };
-use crate::anchor::{
+use triplanetary::Color;
+use super::anchor::{
   MIDDLE_CENTER,
   Anchor,
 };
-use crate::color::Color;
+use super::draw::mq_color;
 
 pub struct Sprite {
   pub pivot_x: i32,
@@ -63,7 +64,7 @@ impl Sprite {
         &self.texture,
         x as f32,
         y as f32,
-        color,
+        mq_color(color),
         DrawTextureParams {
           dest_size: Some(Vec2::new(self.width, self.height)),
           source: Some(self.source),
@@ -0,0 +1,78 @@
+// Native builds read and write save files next to the executable while the
+// browser build hands them to `data/storage.js`, which offers a download for
+// saves and a file picker for loads.
+
+#[cfg(not(target_arch = "wasm32"))]
+mod platform {
+  pub fn store(filename: &str, contents: &str) -> Result<(), String> {
+    std::fs::write(filename, contents).map_err(|e| e.to_string())
+  }
+
+  pub fn request_load(filename: &str) -> Result<Option<String>, String> {
+    match std::fs::read_to_string(filename) {
+      Ok(x) => Ok(Some(x)),
+      Err(e) => Err(e.to_string()),
+    }
+  }
+
+  pub fn poll_load() -> Option<String> {
+    None
+  }
+}
+
+#[cfg(target_arch = "wasm32")]
+mod platform {
+  use std::sync::Mutex;
+
+  static UPLOAD: Mutex<Option<(Vec<u8>, bool)>> = Mutex::new(None);
+
+  extern "C" {
+    fn triplanetary_download(name: *const u8, name_len: usize, data: *const u8, data_len: usize);
+    fn triplanetary_request_upload();
+  }
+
+  #[no_mangle]
+  pub extern "C" fn triplanetary_upload_alloc(length: usize) -> *mut u8 {
+    let mut upload = UPLOAD.lock().unwrap();
+    let (buffer, _) = upload.insert((vec![0; length], false));
+    buffer.as_mut_ptr()
+  }
+
+  #[no_mangle]
+  pub extern "C" fn triplanetary_upload_ready() {
+    if let Some((_, ref mut ready)) = *UPLOAD.lock().unwrap() {
+      *ready = true;
+    }
+  }
+
+  pub fn store(filename: &str, contents: &str) -> Result<(), String> {
+    unsafe {
+      triplanetary_download(filename.as_ptr(), filename.len(), contents.as_ptr(), contents.len());
+    }
+    Ok(())
+  }
+
+  pub fn request_load(_filename: &str) -> Result<Option<String>, String> {
+    unsafe {
+      triplanetary_request_upload();
+    }
+    Ok(None)
+  }
+
+  pub fn poll_load() -> Option<String> {
+    let mut upload = UPLOAD.lock().unwrap();
+    match *upload {
+      Some((_, true)) => {
+        let (buffer, _) = upload.take().unwrap();
+        Some(String::from_utf8_lossy(&buffer).into_owned())
+      },
+      _ => None,
+    }
+  }
+}
+
+pub use platform::{
+  store,
+  request_load,
+  poll_load,
+};
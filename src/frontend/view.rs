@@ -2,11 +2,10 @@ use macroquad::math::{
   IVec2,
   ivec2,
 };
-use super::Hex;
+use triplanetary::Hex;
 
 pub enum HexOrientation {
   Flat,
-  Pointy,
 }
 
 pub struct HexView {
@@ -34,17 +33,10 @@ impl HexView {
           h * (hex.r + hex.r + hex.q),
         )
       },
-      HexOrientation::Pointy => {
-        let w = self.tile_width / 2;
-        let h = self.tile_height * 3 / 4;
-        ivec2(
-          w * (hex.q + hex.q + hex.r),
-          h * hex.r,
-        )
-      },
     }
   }
 
+  #[allow(clippy::wrong_self_convention)]
   pub fn from_pixel(&self, p: IVec2) -> Hex {
     let sqrt_three = f32::sqrt(3.);
     match self.layout {
@@ -57,15 +49,6 @@ impl HexView {
         let r = x * (-1. / 3.) + y * (sqrt_three / 3.);
         round(q, r)
       },
-      HexOrientation::Pointy => {
-        let w = self.tile_width as f32 / sqrt_three;
-        let h = self.tile_height as f32 / 2.;
-        let x = p.x as f32 / w;
-        let y = p.y as f32 / h;
-        let q = x * (sqrt_three / 3.) + y * (-1. / 3.);
-        let r =                         y * ( 2. / 3.);
-        round(q, r)
-      },
     }
   }
 }
@@ -4,9 +4,3 @@ pub use grid::{
   Hex,
   hex,
 };
-
-mod view;
-pub use view::{
-  HexOrientation,
-  HexView,
-};
@@ -1,41 +1,36 @@
-mod anchor;
+#[macro_use]
+mod macros;
+
+mod ai;
 mod color;
 mod component;
 mod counters;
-mod draw;
+mod delta;
 mod entity;
+mod faction;
 mod hex;
 mod math;
-mod resources;
-mod sprite;
+mod ordnance;
+mod replay;
+mod save;
+mod scenario;
+mod simulation;
+mod spawn;
+mod world;
 
-pub use anchor::*;
+pub use ai::*;
 pub use color::*;
 pub use component::*;
 pub use counters::*;
-pub use draw::*;
+pub use delta::*;
 pub use entity::*;
+pub use faction::*;
 pub use hex::*;
 pub use math::*;
-pub use resources::*;
-pub use sprite::*;
-
-pub use macroquad::prelude::{
-  Conf,
-  IVec2,
-  KeyCode,
-  MouseButton,
-  Vec2,
-  clear_background,
-  get_frame_time,
-  is_key_released,
-  is_mouse_button_pressed,
-  is_mouse_button_released,
-  ivec2,
-  measure_text,
-  mouse_position,
-  next_frame,
-  screen_height,
-  screen_width,
-  vec2,
-};
+pub use ordnance::*;
+pub use replay::*;
+pub use save::*;
+pub use scenario::*;
+pub use simulation::*;
+pub use spawn::*;
+pub use world::*;
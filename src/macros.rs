@@ -0,0 +1,28 @@
+#[macro_export]
+macro_rules! must_return {
+  ($x:expr) => {
+    match $x {
+      Some(y) => y,
+      None => return,
+    }
+  };
+}
+
+#[macro_export]
+macro_rules! must_continue {
+  ($x:expr) => {
+    match $x {
+      Some(y) => y,
+      None => continue,
+    }
+  };
+}
+
+#[macro_export]
+macro_rules! instrument {
+  ($counters:expr, $x:expr) => {
+    let handle = $counters.begin(stringify!($x));
+    $x;
+    $counters.end(handle);
+  };
+}
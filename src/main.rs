@@ -1,75 +1,28 @@
-use std::collections::{
-  VecDeque,
-  HashMap,
+mod frontend;
+
+use std::collections::BTreeMap;
+use macroquad::prelude::{
+  Conf,
+  IVec2,
+  KeyCode,
+  MouseButton,
+  clear_background,
+  clear_input_queue,
+  get_char_pressed,
+  get_frame_time,
+  is_key_pressed,
+  is_key_released,
+  is_mouse_button_pressed,
+  is_mouse_button_released,
+  ivec2,
+  measure_text,
+  mouse_position,
+  next_frame,
+  screen_height,
+  screen_width,
 };
 use triplanetary::*;
-
-macro_rules! must_return {
-  ($x:expr) => {
-    match $x {
-      Some(y) => y,
-      None => return,
-    }
-  };
-}
-
-macro_rules! must_continue {
-  ($x:expr) => {
-    match $x {
-      Some(y) => y,
-      None => continue,
-    }
-  };
-}
-
-macro_rules! instrument {
-  ($ctx:expr, $x:expr) => {
-    let handle = $ctx.counters.begin(stringify!($x));
-    $x;
-    $ctx.counters.end(handle);
-  };
-}
-
-#[derive(Copy, Clone, Debug)]
-enum ObjectType {
-  Ship,
-  Asteroid,
-  //Planet,
-  Gravity0,
-  Gravity1,
-  Gravity2,
-  Gravity3,
-  Gravity4,
-  Gravity5,
-}
-
-impl std::fmt::Display for ObjectType {
-  fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-    write!(f, "{self:?}")
-  }
-}
-
-#[derive(Copy, Clone, Debug)]
-struct VisibleObject(ObjectType, Color);
-
-#[derive(Copy, Clone, Debug)]
-enum Nav {
-  Idle,
-  GoTo(Hex),
-  Seek(EntityId),
-}
-
-#[derive(Copy, Clone, Debug)]
-struct HistoryEvent {
-  position: Hex,
-  thrust_applied: i32,
-}
-
-#[derive(Copy, Clone, Debug)]
-struct Engine {
-  power: i32,
-  thrust_applied: i32,
-}
+use frontend::*;
 
 #[derive(Copy, Clone, Debug)]
 struct Camera {
@@ -88,82 +41,24 @@ impl Camera {
   }
 }
 
-
-struct World {
-  seed: u32,
-  camera: Camera,
-  player: Option<EntityId>,
-  vision_radius: i32,
-  visibility: HashMap<Hex, (bool, bool)>,
-  need_simulate: bool,
-  turn: i32,
-  entities: EntityTracker,
-  name: Components<String>,
-  position: Positions,
-  velocity: Components<Hex>,
-  history: Components<VecDeque<HistoryEvent>>,
-  engine: Components<Engine>,
-  visible_object: Components<VisibleObject>,
-  nav: Components<Nav>,
-}
-
-impl World {
-  fn new() -> Box<Self> {
-    Box::new(Self {
-      seed: 0,
-      camera: Camera {
-        center_hex: hex(0, 0),
-        center_pix: ivec2(0, 0),
-        drag: None,
-      },
-      player: None,
-      vision_radius: 50,
-      visibility: HashMap::new(),
-      need_simulate: false,
-      turn: 1,
-      entities: EntityTracker::default(),
-      name: Components::default(),
-      position: Positions::default(),
-      velocity: Components::default(),
-      history: Components::default(),
-      engine: Components::default(),
-      visible_object: Components::default(),
-      nav: Components::default(),
-    })
+impl Persist for Camera {
+  fn write(&self, w: &mut SaveWriter) {
+    w.write(&self.center_hex);
+    w.write(&self.center_pix.x);
+    w.write(&self.center_pix.y);
   }
 
-  pub fn remove(&mut self, entity_id: EntityId) {
-    self.name.del(entity_id);
-    self.position.del(entity_id);
-    self.velocity.del(entity_id);
-    self.history.del(entity_id);
-    self.engine.del(entity_id);
-    self.visible_object.del(entity_id);
-    self.nav.del(entity_id);
-    self.entities.remove(entity_id);
-  }
-}
-
-fn generate_object(seed: u32, position: Hex) -> Option<VisibleObject> {
-  let seed = hash2_u32(seed, 4331);
-  if (position - hex(0, 0)).mag() < 4 {
-    return None;
-  }
-  let scale = 100.;
-  let p = HEX_VIEW.to_pixel(position).as_vec2() / scale;
-  let v = fbm(2, 5., 0.3, seed, p.x, p.y);
-  let low = 0.55;
-  let high = 1.;
-  if v < low || v > high {
-    return None;
+  fn read(r: &mut SaveReader) -> Result<Self, SaveError> {
+    Ok(Camera {
+      center_hex: r.read()?,
+      center_pix: ivec2(r.read()?, r.read()?),
+      drag: None,
+    })
   }
-  let c = remap(v, low, high, 0.3, 0.8);
-  let c = Color{ r: c, g: c, b: c, a: 1. };
-  Some(VisibleObject(ObjectType::Asteroid, c))
 }
 
 fn input_mouse_pan_system(ctx: &mut Context) {
-  let camera = &mut ctx.world.camera;
+  let camera = &mut ctx.camera;
   if let Some(start) = camera.drag {
     camera.center_pix = start - ctx.cursor_screen;
     if is_mouse_button_released(MouseButton::Right) {
@@ -178,211 +73,217 @@ fn input_mouse_pan_system(ctx: &mut Context) {
   }
 }
 
-fn input_player_thrust_system(ctx: &mut Context) {
-  if is_mouse_button_released(MouseButton::Left) {
-    let player_entity_id = must_return!(ctx.world.player);
-    let position = *must_return!(ctx.world.position.get(player_entity_id));
-    let velocity = *must_return!(ctx.world.velocity.get(player_entity_id));
-    let mut engine = *must_return!(ctx.world.engine.get(player_entity_id));
-    let next_position = position + velocity;
-    let desired_thrust = (ctx.cursor_world - next_position).mag();
-    if desired_thrust > engine.power {
+fn input_save_load_system(ctx: &mut Context) {
+  if let Some(text) = poll_load() {
+    load_game(ctx, &text);
+  }
+  let prompt = match ctx.prompt {
+    Some(ref mut prompt) => prompt,
+    None => {
+      if ctx.replay_player.is_some() {
+        return;
+      }
+      let kind = if is_key_pressed(KeyCode::S) {
+        PromptKind::Save
+      } else if is_key_pressed(KeyCode::R) {
+        PromptKind::SaveReplay
+      } else if is_key_pressed(KeyCode::L) {
+        PromptKind::Load
+      } else {
+        return;
+      };
+      clear_input_queue();
+      let filename = match kind {
+        PromptKind::SaveReplay => ctx.replay_filename.clone(),
+        _ => ctx.save_filename.clone(),
+      };
+      ctx.prompt = Some(Prompt {
+        kind,
+        filename,
+      });
       return;
+    },
+  };
+  while let Some(c) = get_char_pressed() {
+    if !c.is_control() {
+      prompt.filename.push(c);
     }
-    ctx.world.engine.set(player_entity_id, {
-      engine.thrust_applied = desired_thrust;
-      engine
-    });
-    ctx.world.velocity.set(player_entity_id, ctx.cursor_world - position);
-    ctx.world.need_simulate = true;
   }
-  if is_key_released(KeyCode::Space) {
-    ctx.world.need_simulate = true;
+  if is_key_pressed(KeyCode::Backspace) {
+    prompt.filename.pop();
   }
-}
-
-fn simulate_nav_system(ctx: &mut Context) {
-  for entity_id in ctx.world.entities.all() {
-    let nav = *must_continue!(ctx.world.nav.get(entity_id));
-    let position = *must_continue!(ctx.world.position.get(entity_id));
-    let velocity = *must_continue!(ctx.world.velocity.get(entity_id));
-    let mut engine = *must_continue!(ctx.world.engine.get(entity_id));
-    let desired_velocity = match nav {
-      Nav::Idle => {
-        hex(0, 0)
-      },
-      Nav::GoTo(target_position) => {
-        let target_vector = target_position - position;
-        let target_length = target_vector.mag() as f32;
-        let desired_speed = target_length.sqrt().floor() as usize;
-        match hex(0, 0).line(target_vector).get(desired_speed) {
-          Some(&Path::One(x)) => x,
-          Some(&Path::Alt(x, _)) => x,
-          None => velocity,
-        }
-      },
-      Nav::Seek(target_entity_id) => {
-        let target_position = *must_continue!(ctx.world.position.get(target_entity_id));
-        let target_velocity = match ctx.world.velocity.get(target_entity_id) {
-          Some(x) => *x,
-          None => hex(0, 0),
-        };
-        let mut a_position = position;
-        let mut b_position = target_position + target_velocity;
-        let mut a_velocity = velocity;
-        while a_velocity != target_velocity {
-          a_velocity = a_velocity.move_to(target_velocity, engine.power);
-          a_position = a_position + a_velocity;
-          b_position = b_position + target_velocity;
-        }
-        b_position - a_position
-      },
-    };
-    let new_velocity = velocity.move_to(desired_velocity, engine.power);
-    ctx.world.engine.set(entity_id, {
-      engine.thrust_applied = (new_velocity - velocity).mag();
-      engine
-    });
-    ctx.world.velocity.set(entity_id, new_velocity);
+  if is_key_pressed(KeyCode::Escape) {
+    ctx.prompt = None;
+    return;
   }
-}
-
-fn simulate_movement_system(ctx: &mut Context) {
-  for entity_id in ctx.world.entities.all() {
-    let start_position = *must_continue!(ctx.world.position.get(entity_id));
-    let velocity = *must_continue!(ctx.world.velocity.get(entity_id));
-    let end_position = start_position + velocity;
-    for step in start_position.line(end_position) {
-      match step {
-        Path::One(a) => {
-          if a == start_position {
-            continue;
-          }
-          let entities_at = match ctx.world.position.at(a) {
-            Some(x) => x,
-            None => continue,
-          };
-          for other in entities_at.to_owned().iter() {
-            simulate_collision_event(ctx, entity_id, *other);
-          }
-        },
-        Path::Alt(a, b) => {
-          let mut entities_at = match ctx.world.position.at(a) {
-            Some(x) => x,
-            None => continue,
-          };
-          if !entities_at.is_empty() {
-            entities_at = match ctx.world.position.at(b) {
-              Some(x) => x,
-              None => continue,
-            }
-          }
-          for other in entities_at.to_owned().iter() {
-            simulate_collision_event(ctx, entity_id, *other);
-          }
-        },
-      }
-    }
-    ctx.world.position.set(entity_id, end_position);
-    if let Some(ref mut history) = ctx.world.history.get_mut(entity_id) {
-      if let Some(engine) = ctx.world.engine.get(entity_id) {
-        if let Some(entry) = history.back_mut() {
-          entry.thrust_applied = engine.thrust_applied;
-        }
-      }
-      history.push_back(HistoryEvent {
-        position: end_position,
-        thrust_applied: 0,
-      });
-      if history.len() > 6 {
-        history.pop_front();
+  if !is_key_pressed(KeyCode::Enter) || prompt.filename.is_empty() {
+    return;
+  }
+  let Prompt { kind, filename } = must_return!(ctx.prompt.take());
+  match kind {
+    PromptKind::Save => {
+      let mut save = ctx.world.save();
+      save.put("camera", &ctx.camera);
+      let text = save.to_text();
+      ctx.status = match store(&filename, &text) {
+        Ok(()) => format!("saved turn {} to {filename}", ctx.world.turn),
+        Err(e) => format!("could not save {filename}: {e}"),
+      };
+      ctx.save_filename = filename;
+    },
+    PromptKind::SaveReplay => {
+      let mut save = ctx.recorder.to_save(&ctx.world);
+      save.put("camera", &ctx.camera);
+      let text = save.to_text();
+      ctx.status = match store(&filename, &text) {
+        Ok(()) => format!("saved replay of turn {} to {filename}", ctx.world.turn),
+        Err(e) => format!("could not save {filename}: {e}"),
+      };
+      ctx.replay_filename = filename;
+    },
+    PromptKind::Load => {
+      match request_load(&filename) {
+        Ok(Some(text)) => load_game(ctx, &text),
+        Ok(None) => {},
+        Err(e) => ctx.status = format!("could not load {filename}: {e}"),
       }
-    }
-    if let Some(engine) = ctx.world.engine.get(entity_id) {
-      let mut engine = *engine;
-      ctx.world.engine.set(entity_id, {
-        engine.thrust_applied = 0;
-        engine
-      });
-    }
+      ctx.save_filename = filename;
+    },
   }
 }
 
-fn simulate_generate_around_player_system(ctx: &mut Context) {
-  let player_entity_id = must_return!(ctx.world.player);
-  let current = match ctx.world.history.get(player_entity_id) {
-    Some(h) => match h.back() {
-      Some(x) => x.position,
-      None => return,
+fn load_game(ctx: &mut Context, text: &str) {
+  let save = match SaveFile::parse(text, SAVE_MIGRATIONS) {
+    Ok(x) => x,
+    Err(e) => {
+      ctx.status = format!("could not load save: {e}");
+      return;
     },
-    None => return,
   };
-  let previous = match ctx.world.history.get(player_entity_id) {
-    Some(h) if h.len() > 1 => h.get(h.len() - 2).map(|x| x.position),
-    _ => None,
+  let camera = save.get("camera").unwrap_or(ctx.camera);
+  let loaded = if Replay::is_replay(&save) {
+    Replay::from_save(save)
+      .and_then(ReplayPlayer::new)
+      .map(|(player, world)| (world, Some(player)))
+  } else {
+    World::load(&save).map(|world| (world, None))
   };
-  if let Some(previous) = previous {
-    if current == previous {
-      return;
-    }
-    for position in previous.spiral(ctx.world.vision_radius) {
-      if (position - current).mag() <= ctx.world.vision_radius {
-        continue;
-      }
-      let entities_at = must_continue!(ctx.world.position.at(position));
-      for entity_id in entities_at.to_owned().iter() {
-        ctx.world.remove(*entity_id);
-      }
-    }
+  match loaded {
+    Ok((world, replay_player)) => {
+      ctx.world = world;
+      ctx.camera = camera;
+      simulate_vision_system(&mut ctx.world);
+      ctx.recorder = Replay::record(&ctx.world);
+      ctx.status = match replay_player {
+        Some(_) => format!("replaying from turn {}", ctx.world.turn),
+        None => format!("loaded turn {}", ctx.world.turn),
+      };
+      ctx.replay_player = replay_player;
+    },
+    Err(e) => ctx.status = format!("could not load save: {e}"),
   }
-  for position in current.spiral(ctx.world.vision_radius) {
-    if let Some(previous) = previous {
-      if (position - previous).mag() <= ctx.world.vision_radius {
-        continue;
-      }
-    }
-    let result = generate_object(ctx.world.seed, position);
-    if let Some(visible_object) = result {
-      let entity_id = ctx.world.entities.create();
-      ctx.world.position.set(entity_id, position);
-      ctx.world.visible_object.set(entity_id, visible_object);
-    }
+}
+
+fn input_replay_system(ctx: &mut Context) {
+  if ctx.replay_player.is_none() {
+    return;
+  }
+  if is_key_pressed(KeyCode::Escape) {
+    let player = must_return!(ctx.replay_player.take());
+    ctx.recorder = player.into_replay();
+    ctx.status = format!("replay stopped on turn {}", ctx.world.turn);
+    return;
+  }
+  ctx.replay_delay -= 1;
+  if ctx.replay_delay > 0 {
+    return;
+  }
+  ctx.replay_delay = REPLAY_FRAMES_PER_TURN;
+  remember_knowledge(ctx);
+  let player = must_return!(ctx.replay_player.as_mut());
+  let status = player.step(&mut ctx.world, &mut ctx.counters);
+  ctx.status = match status {
+    ReplayStatus::Playing => {
+      format!("replaying turn {}", ctx.world.turn)
+    },
+    ReplayStatus::Verified(checksum) => {
+      format!("replay verified on turn {} ({checksum:08x})", ctx.world.turn)
+    },
+    ReplayStatus::Diverged { expected, actual } => {
+      format!("replay diverged: expected {expected:08x} but got {actual:08x}")
+    },
+  };
+  if let ReplayStatus::Playing = status {
+    return;
   }
+  let player = must_return!(ctx.replay_player.take());
+  ctx.recorder = player.into_replay();
 }
 
-fn simulate_vision_system(ctx: &mut Context) {
-  let player_entity_id = must_return!(ctx.world.player);
-  let origin = *must_return!(ctx.world.position.get(player_entity_id));
-  let visibility = &mut ctx.world.visibility;
-  visibility.clear();
-  visibility.insert(origin, (true, false));
-  for position in origin.spiral(ctx.world.vision_radius) {
-    let is_visible_through = |position| {
-      let (visible, obstructed) = *visibility.get(&position).unwrap();
-      visible && !obstructed
-    };
-    let visible = match position.line(origin).get(1) {
-      Some(&Path::One(a)) => is_visible_through(a),
-      Some(&Path::Alt(a, b)) => is_visible_through(a) && is_visible_through(b),
-      None => true,
-    };
-    let obstructed = match ctx.world.position.at(position) {
-      Some(x) => !x.is_empty(),
-      None => false,
-    };
-    visibility.insert(position, (visible, obstructed));
+fn issue_order(ctx: &mut Context, order: Order) -> bool {
+  if !simulate_order(&mut ctx.world, order) {
+    return false;
   }
+  ctx.recorder.push(ctx.world.turn, order);
+  true
 }
 
-fn simulate_step_system(ctx: &mut Context) {
-  if !ctx.world.need_simulate {
+fn input_player_thrust_system(ctx: &mut Context) {
+  if ctx.prompt.is_some() || ctx.replay_player.is_some() {
     return;
   }
-  simulate_event(ctx);
-  ctx.world.need_simulate = false;
-  ctx.world.turn += 1;
+  if is_mouse_button_released(MouseButton::Left) {
+    let player_entity_id = must_return!(ctx.world.player);
+    issue_order(ctx, Order::Thrust(player_entity_id, ctx.cursor_world));
+  }
+  if is_key_released(KeyCode::G) {
+    let player_entity_id = must_return!(ctx.world.player);
+    let ignore_weak = match ctx.world.gravity_choice.get(player_entity_id) {
+      Some(choice) => !choice.ignore_weak,
+      None => true,
+    };
+    issue_order(ctx, Order::IgnoreWeakGravity(player_entity_id, ignore_weak));
+  }
+  if is_key_released(KeyCode::V) {
+    let player_entity_id = must_return!(ctx.world.player);
+    let sensor = *must_return!(ctx.world.sensor.get(player_entity_id));
+    issue_order(ctx, Order::Sensors(player_entity_id, !sensor.active));
+  }
+  if is_key_released(KeyCode::F) {
+    let player_entity_id = must_return!(ctx.world.player);
+    // only what the player's faction has identified can be picked out
+    let targets: Vec<EntityId> = match viewing_faction(ctx) {
+      Some(faction) => faction.contacts.iter()
+        .filter(|(_, contact)| contact.identified && contact.position == ctx.cursor_world)
+        .map(|(entity_id, _)| *entity_id)
+        .collect(),
+      None => must_return!(ctx.world.position.at(ctx.cursor_world)).iter().copied().collect(),
+    };
+    for target_entity_id in targets {
+      if issue_order(ctx, Order::Fire(player_entity_id, target_entity_id)) {
+        break;
+      }
+    }
+  }
+  for (key, kind) in [
+    (KeyCode::T, OrdnanceKind::Torpedo),
+    (KeyCode::M, OrdnanceKind::Mine),
+    (KeyCode::N, OrdnanceKind::Nuke),
+  ] {
+    if !is_key_released(key) {
+      continue;
+    }
+    // torpedoes are boosted towards the cursor as far as they can go
+    let player_entity_id = must_return!(ctx.world.player);
+    let position = *must_return!(ctx.world.position.get(player_entity_id));
+    let course = must_return!(ctx.world.plotted_course(player_entity_id));
+    let boost = hex(0, 0).move_to(ctx.cursor_world - (position + course), kind.boost());
+    issue_order(ctx, Order::Launch(player_entity_id, kind, boost));
+  }
+  if is_key_released(KeyCode::Space) {
+    issue_order(ctx, Order::Commit);
+  }
 }
-
 fn draw_background_hex_grid_system(ctx: &Context) {
   let tw = HEX_WIDTH as f32;
   let th = HEX_HEIGHT as f32;
@@ -392,59 +293,165 @@ fn draw_background_hex_grid_system(ctx: &Context) {
     Some(player_entity_id) => ctx.world.position.get(player_entity_id),
     None => None,
   };
-  let center_hex = ctx.world.camera.world_coords(ivec2(0, 0));
+  let faction = viewing_faction(ctx);
+  let center_hex = ctx.camera.world_coords(ivec2(0, 0));
   for y in -yn..=yn {
     for x in -xn..=xn {
       let p = x;
       let q = y - (x - (x&1)) / 2;
       let world_hex = center_hex + hex(p, q);
-      if let Some(player_hex) = player_hex {
-        if (world_hex - *player_hex).mag() > ctx.world.vision_radius {
-          continue;
-        }
-      }
-      if let Some((visible, _)) = ctx.world.visibility.get(&world_hex) {
-        if !*visible {
-          continue;
-        }
-      }
-      let color = Color::new(0.1, 0.1, 0.2, 1.);
-      let position = ctx.world.camera.screen_coords(world_hex);
+      let color = match faction {
+        // explored hexes out of sight are drawn darker
+        Some(faction) if faction.visible.contains(&world_hex) => Color::new(0.1, 0.1, 0.2, 1.),
+        Some(faction) if faction.explored.contains_key(&world_hex) => Color::new(0.05, 0.05, 0.1, 1.),
+        Some(_) => continue,
+        None => {
+          if let Some(player_hex) = player_hex {
+            if (world_hex - *player_hex).mag() > ctx.world.vision_radius {
+              continue;
+            }
+          }
+          Color::new(0.1, 0.1, 0.2, 1.)
+        },
+      };
+      let position = ctx.camera.screen_coords(world_hex);
       ctx.resources.hex_filled.draw(color, position);
     }
   }
 }
 
+// The faction whose knowledge is drawn: the player's, or everything when the
+// player has no faction.
+fn viewing_faction(ctx: &Context) -> Option<&Faction> {
+  ctx.world.factions.get(&ctx.world.player_faction()?)
+}
+
+// How much the viewing faction knows about an entity.
+#[derive(Copy, Clone)]
+enum Knowledge {
+  Unknown,
+  // something was detected at the hex but not identified
+  Blip(Hex),
+  Known,
+}
+
+// Identified contacts are known and unidentified ones are blips. Otherwise
+// the entity is known only while it is in sight; out of sight the faction
+// goes by what it remembers of the hex.
+fn knowledge(ctx: &Context, entity_id: EntityId) -> Knowledge {
+  let faction = match viewing_faction(ctx) {
+    Some(x) => x,
+    None => return Knowledge::Known,
+  };
+  if let Some(contact) = faction.contacts.get(&entity_id) {
+    return if contact.identified {
+      Knowledge::Known
+    } else {
+      Knowledge::Blip(contact.position)
+    };
+  }
+  let position = match ctx.world.position.get(entity_id) {
+    Some(x) => x,
+    None => return Knowledge::Unknown,
+  };
+  if faction.visible.contains(position) {
+    Knowledge::Known
+  } else {
+    Knowledge::Unknown
+  }
+}
+
 fn draw_visible_objects_system(ctx: &Context) {
   for entity_id in ctx.world.entities.all().rev() {
+    match knowledge(ctx, entity_id) {
+      Knowledge::Known => {},
+      Knowledge::Blip(position) => {
+        ctx.resources.pip_closed.draw(GRAY, ctx.camera.screen_coords(position));
+        continue;
+      },
+      Knowledge::Unknown => continue,
+    }
     let position = *must_continue!(ctx.world.position.get(entity_id));
     let visible_object = *must_continue!(ctx.world.visible_object.get(entity_id));
     let VisibleObject(object_type, color) = visible_object;
-    let position = ctx.world.camera.screen_coords(position);
-    let sprite = match object_type {
-      ObjectType::Ship => &ctx.resources.ship,
-      ObjectType::Asteroid => &ctx.resources.asteroid,
-      ObjectType::Gravity0 => &ctx.resources.gravity_arrow_0,
-      ObjectType::Gravity1 => &ctx.resources.gravity_arrow_1,
-      ObjectType::Gravity2 => &ctx.resources.gravity_arrow_2,
-      ObjectType::Gravity3 => &ctx.resources.gravity_arrow_3,
-      ObjectType::Gravity4 => &ctx.resources.gravity_arrow_4,
-      ObjectType::Gravity5 => &ctx.resources.gravity_arrow_5,
-    };
-    sprite.draw(color, position);
+    let position = ctx.camera.screen_coords(position);
+    draw_object(ctx, object_type, color, position);
   }
 }
 
+// Draws what the viewing faction last saw in explored hexes that are out of
+// sight, dimmed.
+fn draw_remembered_objects_system(ctx: &Context) {
+  let faction = must_return!(viewing_faction(ctx));
+  for (position, sighting) in faction.explored.iter() {
+    if faction.visible.contains(position) {
+      continue;
+    }
+    let screen_position = ctx.camera.screen_coords(*position);
+    for VisibleObject(object_type, color) in sighting.objects.iter() {
+      draw_object(ctx, *object_type, dimmed(*color), screen_position);
+    }
+  }
+}
+
+fn draw_remembered_tooltip_system(ctx: &Context) {
+  let faction = must_return!(viewing_faction(ctx));
+  if faction.visible.contains(&ctx.cursor_world) {
+    return;
+  }
+  let sighting = must_return!(faction.explored.get(&ctx.cursor_world));
+  let text = format!("last seen on turn {}", sighting.turn);
+  macroquad::text::draw_text(
+    &text,
+    ctx.cursor_screen.x as f32 + 16.,
+    ctx.cursor_screen.y as f32 - 8.,
+    24.,
+    mq_color(GRAY)
+  );
+}
+
+fn dimmed(color: Color) -> Color {
+  Color::new(color.r * 0.4, color.g * 0.4, color.b * 0.4, color.a)
+}
+
+fn draw_object(ctx: &Context, object_type: ObjectType, color: Color, position: IVec2) {
+  let sprite = match object_type {
+    ObjectType::Ship => &ctx.resources.ship,
+    ObjectType::Asteroid => &ctx.resources.asteroid,
+    ObjectType::Planet => &ctx.resources.planet,
+    ObjectType::Base => &ctx.resources.base,
+    ObjectType::Torpedo => &ctx.resources.torpedo,
+    ObjectType::Mine => &ctx.resources.mine,
+    ObjectType::Nuke => &ctx.resources.nuke,
+    ObjectType::Gravity0 => &ctx.resources.gravity_arrow_0,
+    ObjectType::Gravity1 => &ctx.resources.gravity_arrow_1,
+    ObjectType::Gravity2 => &ctx.resources.gravity_arrow_2,
+    ObjectType::Gravity3 => &ctx.resources.gravity_arrow_3,
+    ObjectType::Gravity4 => &ctx.resources.gravity_arrow_4,
+    ObjectType::Gravity5 => &ctx.resources.gravity_arrow_5,
+    ObjectType::WeakGravity0 => &ctx.resources.weak_gravity_arrow_0,
+    ObjectType::WeakGravity1 => &ctx.resources.weak_gravity_arrow_1,
+    ObjectType::WeakGravity2 => &ctx.resources.weak_gravity_arrow_2,
+    ObjectType::WeakGravity3 => &ctx.resources.weak_gravity_arrow_3,
+    ObjectType::WeakGravity4 => &ctx.resources.weak_gravity_arrow_4,
+    ObjectType::WeakGravity5 => &ctx.resources.weak_gravity_arrow_5,
+  };
+  sprite.draw(color, position);
+}
+
 fn draw_history_trail_system(ctx: &Context) {
   for entity_id in ctx.world.entities.all() {
+    if !matches!(knowledge(ctx, entity_id), Knowledge::Known) {
+      continue;
+    }
     let history = must_continue!(ctx.world.history.get(entity_id));
     let visible_object = *must_continue!(ctx.world.visible_object.get(entity_id));
     let VisibleObject(_, color) = visible_object;
     for j in 1..history.len() {
       let event_a = history[j - 1];
       let event_b = history[j];
-      let a = ctx.world.camera.screen_coords(event_a.position);
-      let b = ctx.world.camera.screen_coords(event_b.position);
+      let a = ctx.camera.screen_coords(event_a.position);
+      let b = ctx.camera.screen_coords(event_b.position);
       draw_line(color, 2, a, b);
       let pip = if event_a.thrust_applied > 0 {
         &ctx.resources.pip_closed
@@ -454,26 +461,214 @@ fn draw_history_trail_system(ctx: &Context) {
       pip.draw(color, a);
     }
     let position = *must_continue!(ctx.world.position.get(entity_id));
-    let velocity = *must_continue!(ctx.world.velocity.get(entity_id));
-    let next_position = position + velocity;
-    let a = ctx.world.camera.screen_coords(position);
-    let b = ctx.world.camera.screen_coords(next_position);
+    let course = must_continue!(ctx.world.course(entity_id));
+    let next_position = position + course;
+    let a = ctx.camera.screen_coords(position);
+    let b = ctx.camera.screen_coords(next_position);
     draw_line(color, 3, a, b);
   }
 }
 
 fn draw_player_thrust_destination_system(ctx: &Context) {
+  if ctx.world.phase != Phase::Astrogation {
+    return;
+  }
   let player_entity_id = must_return!(ctx.world.player);
   let position = *must_return!(ctx.world.position.get(player_entity_id));
-  let velocity = *must_return!(ctx.world.velocity.get(player_entity_id));
+  let course = must_return!(ctx.world.course(player_entity_id));
   let engine = *must_return!(ctx.world.engine.get(player_entity_id));
-  let next_position = position + velocity;
-  for neighbor in next_position.spiral(engine.power) {
-    let screen_position = ctx.world.camera.screen_coords(neighbor);
-    ctx.resources.hex_empty.draw(DARK_YELLOW, screen_position);
+  let thrust_limit = must_return!(ctx.world.thrust_limit(player_entity_id));
+  let next_position = position + course;
+  for neighbor in next_position.spiral(thrust_limit) {
+    let thrust = (neighbor - next_position).mag();
+    let overload = thrust > ctx.world.free_thrust(player_entity_id) + engine.power;
+    let color = if overload { RED } else { DARK_YELLOW };
+    let screen_position = ctx.camera.screen_coords(neighbor);
+    ctx.resources.hex_empty.draw(color, screen_position);
+  }
+  let plotted_course = must_return!(ctx.world.plotted_course(player_entity_id));
+  let screen_position = ctx.camera.screen_coords(position + plotted_course);
+  ctx.resources.hex_filled.draw(DARK_YELLOW, screen_position);
+}
+
+fn draw_player_target_system(ctx: &Context) {
+  let player_entity_id = must_return!(ctx.world.player);
+  let target_entity_id = *must_return!(ctx.world.target.get(player_entity_id));
+  let a = *must_return!(ctx.world.position.get(player_entity_id));
+  // the line goes to where the faction last placed the target, not where it
+  // really is
+  let b = match viewing_faction(ctx) {
+    Some(faction) => must_return!(faction.contacts.get(&target_entity_id)).position,
+    None => *must_return!(ctx.world.position.get(target_entity_id)),
+  };
+  let color = if line_of_sight(&ctx.world, a, b) { RED } else { GRAY };
+  draw_line(color, 2, ctx.camera.screen_coords(a), ctx.camera.screen_coords(b));
+}
+
+fn draw_player_disabled_system(ctx: &Context) {
+  let player_entity_id = must_return!(ctx.world.player);
+  let turns = ctx.world.disabled_turns(player_entity_id);
+  if turns == 0 {
+    return;
+  }
+  let text = format!("disabled for {turns} more turns, engines offline");
+  macroquad::text::draw_text(
+    &text,
+    30.,
+    screen_height() - 90.,
+    30.,
+    mq_color(RED)
+  );
+}
+
+// Remembers what the viewing faction knows of every entity before the world
+// is stepped, so events can still be told the way the faction saw them after
+// the entities taking part are gone.
+fn remember_knowledge(ctx: &mut Context) {
+  if ctx.world.phase == Phase::Astrogation {
+    ctx.event_knowledge.clear();
+  }
+  for entity_id in ctx.world.entities.all() {
+    if ctx.world.position.has(entity_id) {
+      let known = knowledge(ctx, entity_id);
+      ctx.event_knowledge.insert(entity_id, known);
+    }
+  }
+}
+
+fn event_knowledge(ctx: &Context, entity_id: EntityId) -> Knowledge {
+  if ctx.world.position.has(entity_id) {
+    return knowledge(ctx, entity_id);
+  }
+  ctx.event_knowledge.get(&entity_id).copied().unwrap_or(Knowledge::Unknown)
+}
+
+// Events only name what the viewing faction has identified. Anything else
+// taking part is an unknown contact, and events nobody known took part in
+// are not told at all.
+fn describe_event(ctx: &Context, event: &GameEvent) -> Option<String> {
+  let known = |entity_id| match event_knowledge(ctx, entity_id) {
+    Knowledge::Known => Some(ctx.world.display_name(entity_id)),
+    Knowledge::Blip(_) => Some("unknown contact".to_string()),
+    Knowledge::Unknown => None,
+  };
+  let participants: Vec<EntityId> = match event {
+    GameEvent::PhaseChanged { .. } => vec![],
+    GameEvent::Rammed { entity_id, other, .. } | GameEvent::Docked { entity_id, other } => vec![*entity_id, *other],
+    GameEvent::NoIntercept { entity_id, target } | GameEvent::Fired { entity_id, target, .. } => vec![*entity_id, *target],
+    GameEvent::AsteroidHazard { entity_id, .. }
+      | GameEvent::Crashed { entity_id, .. }
+      | GameEvent::Detonated { entity_id, .. }
+      | GameEvent::CaughtInBlast { entity_id, .. }
+      | GameEvent::Destroyed { entity_id, .. } => vec![*entity_id],
+  };
+  if !participants.is_empty() && participants.iter().all(|x| known(*x).is_none()) {
+    return None;
+  }
+  let name = |entity_id| known(entity_id).unwrap_or_else(|| "unknown contact".to_string());
+  Some(match event {
+    GameEvent::PhaseChanged { turn, to, .. } => format!("turn {turn}: {} phase", to.to_string().to_lowercase()),
+    GameEvent::AsteroidHazard { entity_id, roll, result, .. } => {
+      format!("{} hit asteroids, rolled {roll}: {result:?}", name(*entity_id))
+    },
+    GameEvent::Rammed { entity_id, other, relative_speed, roll, result } => {
+      format!("{} rammed by {} at speed {relative_speed}, rolled {roll}: {result:?}", name(*entity_id), name(*other))
+    },
+    GameEvent::NoIntercept { entity_id, target } => {
+      format!("{} cannot intercept {}", name(*entity_id), name(*target))
+    },
+    GameEvent::Docked { entity_id, other } => format!("{} docked with {}", name(*entity_id), name(*other)),
+    GameEvent::Crashed { entity_id, .. } => format!("{} crashed into a planet", name(*entity_id)),
+    GameEvent::Fired { entity_id, target, roll, modifier, result } => {
+      format!("{} fired at {}, rolled {roll}{modifier:+}: {result:?}", name(*entity_id), name(*target))
+    },
+    GameEvent::Detonated { entity_id, .. } => format!("{} detonated", name(*entity_id)),
+    GameEvent::CaughtInBlast { entity_id, roll: Some(roll), result } => {
+      format!("{} caught in the blast, rolled {roll}: {result:?}", name(*entity_id))
+    },
+    GameEvent::CaughtInBlast { entity_id, roll: None, result } => {
+      format!("{} caught in the blast: {result:?}", name(*entity_id))
+    },
+    GameEvent::Destroyed { entity_id, name: destroyed } => match event_knowledge(ctx, *entity_id) {
+      Knowledge::Known => format!("{destroyed} was destroyed"),
+      _ => "unknown contact was destroyed".to_string(),
+    },
+  })
+}
+
+fn draw_phase_prompt_system(ctx: &Context) {
+  let prompt = match ctx.world.phase {
+    Phase::Astrogation => "click a hex to plot a burn, G toggles weak gravity, Space to continue",
+    Phase::Ordnance => "T launches a torpedo towards the cursor, M a mine, N a nuke, Space to continue",
+    Phase::Combat => "F fires at the ship under the cursor, Space to continue",
+    Phase::Movement | Phase::Logistics => "",
+  };
+  let text = format!("turn {} {}: {prompt}", ctx.world.turn, ctx.world.phase.to_string().to_lowercase());
+  macroquad::text::draw_text(
+    &text,
+    30.,
+    screen_height() - 120.,
+    30.,
+    mq_color(GREEN)
+  );
+}
+
+fn draw_events_system(ctx: &Context) {
+  let lines = ctx.world.events.iter().filter_map(|x| describe_event(ctx, x));
+  for (i, text) in lines.enumerate() {
+    let size = measure_text(&text, None, 24, 1.);
+    macroquad::text::draw_text(
+      &text,
+      screen_width() - size.width,
+      90. + 24. * i as f32,
+      24.,
+      mq_color(DARK_YELLOW)
+    );
   }
 }
 
+fn draw_player_fuel_system(ctx: &Context) {
+  let player_entity_id = must_return!(ctx.world.player);
+  let engine = must_return!(ctx.world.engine.get(player_entity_id));
+  let mut text = format!("fuel {}/{}", engine.fuel, engine.fuel_capacity);
+  if let Some(magazine) = ctx.world.magazine.get(player_entity_id) {
+    text.push_str(&format!(
+      "  torpedoes {}  mines {}  nukes {}",
+      magazine.count(OrdnanceKind::Torpedo),
+      magazine.count(OrdnanceKind::Mine),
+      magazine.count(OrdnanceKind::Nuke),
+    ));
+  }
+  if let Some(sensor) = ctx.world.sensor.get(player_entity_id) {
+    text.push_str(if sensor.active { "  sensors active" } else { "  sensors passive" });
+  }
+  let size = measure_text(&text, None, 30, 1.);
+  let color = if engine.fuel == 0 { RED } else { GREEN };
+  macroquad::text::draw_text(
+    &text,
+    screen_width() - size.width,
+    60.,
+    30.,
+    mq_color(color)
+  );
+}
+
+fn draw_player_gravity_choice_system(ctx: &Context) {
+  let player_entity_id = must_return!(ctx.world.player);
+  let choice = must_return!(ctx.world.gravity_choice.get(player_entity_id));
+  if !choice.ignore_weak {
+    return;
+  }
+  let text = "ignoring the next weak gravity hex (G to toggle)";
+  macroquad::text::draw_text(
+    text,
+    30.,
+    screen_height() - 60.,
+    30.,
+    mq_color(DARK_YELLOW)
+  );
+}
+
 fn draw_player_to_cursor_hexes_system(ctx: &Context) {
   let color = GREEN;
   if let Some(player_entity_id) = ctx.world.player {
@@ -481,112 +676,109 @@ fn draw_player_to_cursor_hexes_system(ctx: &Context) {
     for step in player_hex.line(ctx.cursor_world) {
       match step {
         Path::One(a) => {
-          let a = ctx.world.camera.screen_coords(a);
+          let a = ctx.camera.screen_coords(a);
           ctx.resources.hex_empty.draw(color, a);
         },
         Path::Alt(a, b) => {
-          let a = ctx.world.camera.screen_coords(a);
-          let b = ctx.world.camera.screen_coords(b);
+          let a = ctx.camera.screen_coords(a);
+          let b = ctx.camera.screen_coords(b);
           ctx.resources.hex_empty.draw(color, a);
           ctx.resources.hex_empty.draw(color, b);
         },
       }
     }
   } else {
-    let cursor_pix = ctx.world.camera.screen_coords(ctx.cursor_world);
+    let cursor_pix = ctx.camera.screen_coords(ctx.cursor_world);
     ctx.resources.hex_empty.draw(color, cursor_pix);
   }
 }
 
+#[derive(Copy, Clone, Debug)]
+enum PromptKind {
+  Save,
+  SaveReplay,
+  Load,
+}
+
+const REPLAY_FRAMES_PER_TURN: i32 = 10;
+
+struct Prompt {
+  kind: PromptKind,
+  filename: String,
+}
+
 struct Context {
   resources: Resources,
   world: Box<World>,
+  camera: Camera,
   cursor_screen: IVec2,
   cursor_world: Hex,
   counters: Counters,
   smooth_frame_time: f32,
-}
-
-fn initialize(ctx: &mut Context) {
-  let world = &mut ctx.world;
-  //world.seed = macroquad::rand::rand();
-  world.seed = 12;
-  let player_entity_id = world.entities.create();
-  let player_starting_position = hex(0, 0);
-  fn new_history(position: Hex) -> VecDeque<HistoryEvent> {
-    vec![
-      HistoryEvent {
-        position,
-        thrust_applied: 0,
-      }
-    ].into_iter().collect()
-  }
-  {
-    world.name.set(player_entity_id, "player".to_string());
-    world.position.set(player_entity_id, player_starting_position);
-    world.velocity.set(player_entity_id, hex(0, 0));
-    world.history.set(player_entity_id, new_history(player_starting_position));
-    world.visible_object.set(player_entity_id, VisibleObject(ObjectType::Ship, GREEN));
-    world.engine.set(player_entity_id, Engine { power: 1, thrust_applied: 0 });
-    world.player = Some(player_entity_id);
-  }
-  {
-    let enemy_starting_position = hex(-20, 20);
-    let entity_id = world.entities.create();
-    world.name.set(entity_id, "enemy".to_string());
-    world.position.set(entity_id, enemy_starting_position);
-    world.velocity.set(entity_id, hex(0, 0));
-    world.history.set(entity_id, new_history(enemy_starting_position));
-    world.visible_object.set(entity_id, VisibleObject(ObjectType::Ship, RED));
-    world.engine.set(entity_id, Engine { power: 1, thrust_applied: 0 });
-    //world.nav.set(entity_id, Nav::Idle);
-    //world.nav.set(entity_id, Nav::GoTo(hex(-15, 15)));
-    world.nav.set(entity_id, Nav::Seek(player_entity_id));
-  }
-  {
-    let planet_position =hex(4, -1);
-    let mut add_gravity = |direction| {
-      let entity_id = world.entities.create();
-      world.position.set(entity_id, planet_position + Hex::direction(direction));
-      let object_type = match direction {
-        3 => ObjectType::Gravity0,
-        4 => ObjectType::Gravity1,
-        5 => ObjectType::Gravity2,
-        0 => ObjectType::Gravity3,
-        1 => ObjectType::Gravity4,
-        2 => ObjectType::Gravity5,
-        _ => panic!(),
-      };
-      world.visible_object.set(entity_id, VisibleObject(object_type, WHITE));
-    };
-    add_gravity(0);
-    add_gravity(1);
-    add_gravity(2);
-    add_gravity(3);
-    add_gravity(4);
-    add_gravity(5);
-  }
-  simulate_generate_around_player_system(ctx);
-  simulate_vision_system(ctx);
+  prompt: Option<Prompt>,
+  save_filename: String,
+  replay_filename: String,
+  recorder: Replay,
+  replay_player: Option<ReplayPlayer>,
+  replay_delay: i32,
+  status: String,
+  // what the viewing faction knew of the entities in this turn's events,
+  // kept after they leave the world
+  event_knowledge: BTreeMap<EntityId, Knowledge>,
 }
 
 fn tick_event(ctx: &mut Context) {
   // input systems
-  instrument!(ctx, input_mouse_pan_system(ctx));
-  instrument!(ctx, input_player_thrust_system(ctx));
+  instrument!(ctx.counters, input_mouse_pan_system(ctx));
+  instrument!(ctx.counters, input_save_load_system(ctx));
+  instrument!(ctx.counters, input_replay_system(ctx));
+  instrument!(ctx.counters, input_player_thrust_system(ctx));
   // simulation systems
-  instrument!(ctx, simulate_step_system(ctx));
+  instrument!(ctx.counters, simulate_world_step_system(ctx));
   // Drawing systems
-  instrument!(ctx, draw_background_hex_grid_system(ctx));
-  instrument!(ctx, draw_history_trail_system(ctx));
-  instrument!(ctx, draw_visible_objects_system(ctx));
-  instrument!(ctx, draw_player_thrust_destination_system(ctx));
-  instrument!(ctx, draw_player_to_cursor_hexes_system(ctx));
+  instrument!(ctx.counters, draw_background_hex_grid_system(ctx));
+  instrument!(ctx.counters, draw_history_trail_system(ctx));
+  instrument!(ctx.counters, draw_remembered_objects_system(ctx));
+  instrument!(ctx.counters, draw_visible_objects_system(ctx));
+  instrument!(ctx.counters, draw_player_thrust_destination_system(ctx));
+  instrument!(ctx.counters, draw_player_to_cursor_hexes_system(ctx));
+  instrument!(ctx.counters, draw_player_gravity_choice_system(ctx));
+  instrument!(ctx.counters, draw_player_fuel_system(ctx));
+  instrument!(ctx.counters, draw_player_target_system(ctx));
+  instrument!(ctx.counters, draw_player_disabled_system(ctx));
+  instrument!(ctx.counters, draw_remembered_tooltip_system(ctx));
+  instrument!(ctx.counters, draw_events_system(ctx));
+  instrument!(ctx.counters, draw_phase_prompt_system(ctx));
+  instrument!(ctx.counters, draw_save_prompt_system(ctx));
   // Debug systems
   debug_fps_system(ctx);
   debug_frame_counters_system(ctx);
 }
 
+fn simulate_world_step_system(ctx: &mut Context) {
+  if !ctx.world.need_simulate {
+    return;
+  }
+  remember_knowledge(ctx);
+  simulate_step_system(&mut ctx.world, &mut ctx.counters);
+}
+
+fn draw_save_prompt_system(ctx: &Context) {
+  let text = match ctx.prompt {
+    Some(Prompt { kind: PromptKind::Save, ref filename }) => format!("save as: {filename}_"),
+    Some(Prompt { kind: PromptKind::SaveReplay, ref filename }) => format!("save replay as: {filename}_"),
+    Some(Prompt { kind: PromptKind::Load, ref filename }) => format!("load from: {filename}_"),
+    None => ctx.status.clone(),
+  };
+  macroquad::text::draw_text(
+    &text,
+    30.,
+    screen_height() - 30.,
+    30.,
+    mq_color(GREEN)
+  );
+}
+
 fn debug_fps_system(ctx: &mut Context) {
   let t = 0.01;
   ctx.smooth_frame_time = t * get_frame_time() + (1. - t) * ctx.smooth_frame_time;
@@ -598,7 +790,7 @@ fn debug_fps_system(ctx: &mut Context) {
     screen_width() - size.width,
     30.,
     30.,
-    GREEN
+    mq_color(GREEN)
   );
 }
 
@@ -609,62 +801,12 @@ fn debug_frame_counters_system(ctx: &mut Context) {
       30.,
       30. + 30. * i as f32,
       30.,
-      GREEN
+      mq_color(GREEN)
     );
   }
   ctx.counters.reset();
 }
 
-fn simulate_event(ctx: &mut Context) {
-  instrument!(ctx, simulate_nav_system(ctx));
-  instrument!(ctx, simulate_movement_system(ctx));
-  instrument!(ctx, simulate_generate_around_player_system(ctx));
-  instrument!(ctx, simulate_vision_system(ctx));
-}
-
-fn simulate_collision_event(ctx: &mut Context, a: EntityId, b: EntityId) {
-  let VisibleObject(type_b, _) = match ctx.world.visible_object.get(b) {
-    Some(x) => *x,
-    None => return,
-  };
-  match type_b {
-    ObjectType::Ship => simulate_collision_ship(ctx, a),
-    ObjectType::Asteroid => simulate_collision_asteroid(ctx, a),
-    ObjectType::Gravity0 => simulate_collision_gravity(ctx, 0, a),
-    ObjectType::Gravity1 => simulate_collision_gravity(ctx, 1, a),
-    ObjectType::Gravity2 => simulate_collision_gravity(ctx, 2, a),
-    ObjectType::Gravity3 => simulate_collision_gravity(ctx, 3, a),
-    ObjectType::Gravity4 => simulate_collision_gravity(ctx, 4, a),
-    ObjectType::Gravity5 => simulate_collision_gravity(ctx, 5, a),
-  }
-}
-
-fn simulate_collision_asteroid(ctx: &mut Context, entity_id: EntityId) {
-  match ctx.world.name.get(entity_id) {
-    Some(x) => println!("{x} collided with an asteroid"),
-    None => println!("{entity_id} collided with an asteroid"),
-  };
-}
-
-fn simulate_collision_ship(ctx: &mut Context, entity_id: EntityId) {
-  match ctx.world.name.get(entity_id) {
-    Some(x) => println!("{x} collided with a ship"),
-    None => println!("{entity_id} collided with a ship"),
-  };
-}
-
-fn simulate_collision_gravity(ctx: &mut Context, direction: i32, entity_id: EntityId) {
-  match ctx.world.name.get(entity_id) {
-    Some(x) => println!("{x} passed through gravity well"),
-    None => println!("{entity_id} passed through gravity well"),
-  };
-  let velocity = match ctx.world.velocity.get(entity_id) {
-    Some(x) => *x,
-    None => return,
-  };
-  ctx.world.velocity.set(entity_id, velocity + Hex::direction(direction));
-}
-
 fn window_conf() -> Conf {
   Conf {
     window_title: "Triplanetary".to_owned(),
@@ -674,22 +816,48 @@ fn window_conf() -> Conf {
 
 #[macroquad::main(window_conf)]
 async fn main() {
+  let mut world = World::new();
+  // a new map every game, the seed is saved along with the world
+  initialize(&mut world, macroquad::miniquad::date::now() as u32);
   let mut ctx = Context {
     resources: Resources::load().await,
-    world: World::new(),
+    recorder: Replay::record(&world),
+    world,
+    camera: Camera {
+      center_hex: hex(0, 0),
+      center_pix: ivec2(0, 0),
+      drag: None,
+    },
     cursor_screen: ivec2(0, 0),
     cursor_world: hex(0, 0),
-    counters: Default::default(),
+    counters: Counters::new(macroquad::time::get_time),
     smooth_frame_time: 0.,
+    prompt: None,
+    save_filename: "triplanetary.sav".to_string(),
+    replay_filename: "triplanetary.replay".to_string(),
+    replay_player: None,
+    replay_delay: 0,
+    status: String::new(),
+    event_knowledge: BTreeMap::new(),
   };
-  initialize(&mut ctx);
+  let mut args = std::env::args().skip(1);
+  while let Some(arg) = args.next() {
+    if arg == "--replay" {
+      let filename = must_continue!(args.next());
+      match request_load(&filename) {
+        Ok(Some(text)) => load_game(&mut ctx, &text),
+        Ok(None) => {},
+        Err(e) => ctx.status = format!("could not load {filename}: {e}"),
+      }
+    }
+  }
   loop {
     ctx.cursor_screen = {
       let (x, y) = mouse_position();
       ivec2(x as i32, y as i32) - MIDDLE_CENTER.window_offset()
     };
-    ctx.cursor_world = ctx.world.camera.world_coords(ctx.cursor_screen);
-    clear_background(BLACK);
+    ctx.cursor_world = ctx.camera.world_coords(ctx.cursor_screen);
+    clear_background(mq_color(BLACK));
     tick_event(&mut ctx);
     next_frame().await
   }
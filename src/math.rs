@@ -1,3 +1,11 @@
+use std::collections::BTreeMap;
+use crate::save::{
+  Persist,
+  SaveError,
+  SaveReader,
+  SaveWriter,
+};
+
 pub const fn hash_u32(x: u32) -> u32 {
   let mut v = x;
   v ^= v.wrapping_shr(16);
@@ -16,6 +24,16 @@ pub const fn hash2_u32(x: u32, y: u32) -> u32 {
   y ^ v
 }
 
+pub fn hash_bytes_u32(seed: u32, bytes: &[u8]) -> u32 {
+  let mut v = hash_u32(seed);
+  for chunk in bytes.chunks(4) {
+    let mut word = [0; 4];
+    word[..chunk.len()].copy_from_slice(chunk);
+    v = hash2_u32(v, u32::from_le_bytes(word));
+  }
+  hash2_u32(v, bytes.len() as u32)
+}
+
 pub fn hash_f32(seed: u32, x: f32) -> f32 {
   let v = hash2_u32(seed, x.to_bits()) as f32;
   v / (u32::MAX as f32)
@@ -93,3 +111,117 @@ pub fn fbm(octaves: i32, lacunarity: f32, gain: f32, seed: u32, x: f32, y: f32)
   }
   value / max
 }
+
+// PCG-XSH-RR 32 bit generator. Its whole state is two integers so it can be
+// written to saves and replays and resumed exactly.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Rng {
+  state: u64,
+  increment: u64,
+}
+
+impl Rng {
+  pub fn new(seed: u64, stream: u64) -> Rng {
+    let mut rng = Rng {
+      state: 0,
+      increment: (stream << 1) | 1,
+    };
+    rng.next_u32();
+    rng.state = rng.state.wrapping_add(seed);
+    rng.next_u32();
+    rng
+  }
+
+  pub fn next_u32(&mut self) -> u32 {
+    let state = self.state;
+    self.state = state
+      .wrapping_mul(6364136223846793005)
+      .wrapping_add(self.increment);
+    let xorshifted = (((state >> 18) ^ state) >> 27) as u32;
+    let rotation = (state >> 59) as u32;
+    xorshifted.rotate_right(rotation)
+  }
+
+  pub fn next_f32(&mut self) -> f32 {
+    (self.next_u32() >> 8) as f32 / (1 << 24) as f32
+  }
+
+  pub fn range(&mut self, low: i32, high: i32) -> i32 {
+    if high <= low {
+      return low;
+    }
+    let span = (high - low) as u32;
+    // reject the tail of the range that would bias the modulo
+    let limit = u32::MAX - u32::MAX % span;
+    loop {
+      let x = self.next_u32();
+      if x < limit {
+        return low + (x % span) as i32;
+      }
+    }
+  }
+
+  pub fn roll(&mut self, sides: i32) -> i32 {
+    self.range(1, sides + 1)
+  }
+}
+
+impl Persist for Rng {
+  fn write(&self, w: &mut SaveWriter) {
+    w.write(&self.state);
+    w.write(&self.increment);
+  }
+
+  fn read(r: &mut SaveReader) -> Result<Self, SaveError> {
+    Ok(Rng {
+      state: r.read()?,
+      increment: r.read()?,
+    })
+  }
+}
+
+// Independent generators keyed by name, so that adding a roll to one system
+// never shifts the sequence seen by another.
+#[derive(Clone, Debug, Default)]
+pub struct RngStreams {
+  streams: BTreeMap<String, Rng>,
+  // mixed into streams started after a fork
+  salt: u32,
+}
+
+impl RngStreams {
+  pub fn get(&mut self, seed: u32, name: &str) -> &mut Rng {
+    let salt = self.salt;
+    self.streams.entry(name.to_string()).or_insert_with(|| {
+      let stream = hash_bytes_u32(seed, name.as_bytes());
+      let seed = ((hash2_u32(seed, stream) as u64) << 32) | seed as u64;
+      Rng::new(seed ^ salt as u64, stream as u64)
+    })
+  }
+
+  // A copy whose streams carry on with different rolls, for trying out
+  // futures without touching the real ones.
+  pub fn fork(&self, salt: u32) -> RngStreams {
+    let streams = self.streams.iter().map(|(name, rng)| {
+      let salt_bits = ((hash2_u32(salt, rng.increment as u32) as u64) << 32) | salt as u64;
+      (name.clone(), Rng::new(rng.state ^ salt_bits, rng.increment >> 1))
+    }).collect();
+    RngStreams {
+      streams,
+      salt: hash2_u32(self.salt, salt),
+    }
+  }
+}
+
+impl Persist for RngStreams {
+  fn write(&self, w: &mut SaveWriter) {
+    w.write(&self.streams);
+  }
+
+  fn read(r: &mut SaveReader) -> Result<Self, SaveError> {
+    Ok(RngStreams {
+      streams: r.read()?,
+      salt: 0,
+    })
+  }
+}
@@ -0,0 +1,175 @@
+use std::collections::BTreeMap;
+use std::sync::OnceLock;
+use crate::entity::EntityId;
+use crate::save::{
+  Persist,
+  SaveError,
+  SaveReader,
+  SaveWriter,
+  save_error,
+};
+use crate::world::ObjectType;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum OrdnanceKind {
+  Torpedo,
+  Mine,
+  Nuke,
+}
+
+impl OrdnanceKind {
+  pub fn object_type(self) -> ObjectType {
+    match self {
+      OrdnanceKind::Torpedo => ObjectType::Torpedo,
+      OrdnanceKind::Mine => ObjectType::Mine,
+      OrdnanceKind::Nuke => ObjectType::Nuke,
+    }
+  }
+
+  // How far a launch may push the ordnance off the launcher's course.
+  pub fn boost(self) -> i32 {
+    match self {
+      OrdnanceKind::Torpedo => 2,
+      OrdnanceKind::Mine => 0,
+      OrdnanceKind::Nuke => 0,
+    }
+  }
+
+  // Attack strength rolled against the defence of everything caught in the
+  // blast, `None` for ordnance that simply destroys it.
+  pub fn attack(self) -> Option<i32> {
+    match self {
+      OrdnanceKind::Torpedo => Some(4),
+      OrdnanceKind::Mine => Some(4),
+      OrdnanceKind::Nuke => None,
+    }
+  }
+}
+
+impl std::fmt::Display for OrdnanceKind {
+  fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+    write!(f, "{self:?}")
+  }
+}
+
+impl Persist for OrdnanceKind {
+  fn write(&self, w: &mut SaveWriter) {
+    w.token(&self.to_string());
+  }
+
+  fn read(r: &mut SaveReader) -> Result<Self, SaveError> {
+    match r.token()? {
+      "Torpedo" => Ok(OrdnanceKind::Torpedo),
+      "Mine" => Ok(OrdnanceKind::Mine),
+      "Nuke" => Ok(OrdnanceKind::Nuke),
+      x => save_error(format!("unknown ordnance kind {x:?}")),
+    }
+  }
+}
+
+// Ordnance burns out this many turns after it is launched.
+pub const ORDNANCE_LIFETIME: i32 = 5;
+
+#[derive(Copy, Clone, Debug)]
+pub struct Ordnance {
+  pub kind: OrdnanceKind,
+  pub launcher: EntityId,
+  pub launch_turn: i32,
+}
+
+impl Persist for Ordnance {
+  fn write(&self, w: &mut SaveWriter) {
+    w.write(&self.kind);
+    w.write(&self.launcher);
+    w.write(&self.launch_turn);
+  }
+
+  fn read(r: &mut SaveReader) -> Result<Self, SaveError> {
+    Ok(Ordnance {
+      kind: r.read()?,
+      launcher: r.read()?,
+      launch_turn: r.read()?,
+    })
+  }
+}
+
+pub struct ShipClass {
+  pub name: String,
+  pub launches_per_turn: i32,
+  pub ordnance: Vec<(OrdnanceKind, i32)>,
+}
+
+// The classes are data rather than code so that they can be tuned without
+// touching the rules.
+const SHIP_CLASSES: &str = include_str!("../data/ship_classes.txt");
+
+pub fn ship_classes() -> &'static [ShipClass] {
+  static CLASSES: OnceLock<Vec<ShipClass>> = OnceLock::new();
+  CLASSES.get_or_init(|| match parse_ship_classes(SHIP_CLASSES) {
+    Ok(x) => x,
+    Err(e) => panic!("data/ship_classes.txt: {}", e),
+  })
+}
+
+pub fn ship_class(name: &str) -> Option<&'static ShipClass> {
+  ship_classes().iter().find(|x| x.name == name)
+}
+
+// One class per line: its name, how many launches it makes per turn, then
+// each kind of ordnance it carries followed by how many. Blank lines and
+// lines starting with `#` are skipped.
+pub fn parse_ship_classes(text: &str) -> Result<Vec<ShipClass>, SaveError> {
+  let mut classes = vec![];
+  for line in text.lines().map(str::trim) {
+    if line.is_empty() || line.starts_with('#') {
+      continue;
+    }
+    let tokens = line.split_whitespace().count();
+    if tokens < 2 || tokens % 2 != 0 {
+      return save_error(format!("malformed ship class {line:?}"));
+    }
+    let mut r = SaveReader::new(line);
+    let name = r.token()?.to_string();
+    let launches_per_turn = r.read()?;
+    let mut ordnance = vec![];
+    for _ in 0..(tokens - 2) / 2 {
+      ordnance.push((r.read()?, r.read()?));
+    }
+    classes.push(ShipClass {
+      name,
+      launches_per_turn,
+      ordnance,
+    });
+  }
+  Ok(classes)
+}
+
+// Ordnance left aboard a ship, filled from its class when it is spawned.
+#[derive(Clone, Debug, Default)]
+pub struct Magazine {
+  pub loaded: BTreeMap<OrdnanceKind, i32>,
+}
+
+impl Magazine {
+  pub fn full(class: &ShipClass) -> Self {
+    Self {
+      loaded: class.ordnance.iter().copied().collect(),
+    }
+  }
+
+  pub fn count(&self, kind: OrdnanceKind) -> i32 {
+    self.loaded.get(&kind).copied().unwrap_or(0)
+  }
+}
+
+impl Persist for Magazine {
+  fn write(&self, w: &mut SaveWriter) {
+    w.write(&self.loaded);
+  }
+
+  fn read(r: &mut SaveReader) -> Result<Self, SaveError> {
+    Ok(Magazine {
+      loaded: r.read()?,
+    })
+  }
+}
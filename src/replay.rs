@@ -0,0 +1,138 @@
+use crate::counters::Counters;
+use crate::save::{
+  Persist,
+  SaveError,
+  SaveFile,
+  SaveReader,
+  SaveWriter,
+};
+use crate::simulation::{
+  Order,
+  simulate_order,
+  simulate_step_system,
+  simulate_vision_system,
+};
+use crate::world::World;
+
+#[derive(Copy, Clone, Debug)]
+pub struct RecordedOrder {
+  pub turn: i32,
+  pub order: Order,
+}
+
+impl Persist for RecordedOrder {
+  fn write(&self, w: &mut SaveWriter) {
+    w.write(&self.turn);
+    w.write(&self.order);
+    w.newline();
+  }
+
+  fn read(r: &mut SaveReader) -> Result<Self, SaveError> {
+    Ok(RecordedOrder {
+      turn: r.read()?,
+      order: r.read()?,
+    })
+  }
+}
+
+// A replay is a save of the world at the moment recording started plus every
+// order given since, so it can be stored in the same file format and loaded
+// as a regular save as well.
+pub struct Replay {
+  pub start: SaveFile,
+  pub orders: Vec<RecordedOrder>,
+  pub checksum: Option<u32>,
+}
+
+impl Replay {
+  pub fn record(world: &World) -> Replay {
+    Replay {
+      start: world.save(),
+      orders: vec![],
+      checksum: None,
+    }
+  }
+
+  pub fn is_replay(save: &SaveFile) -> bool {
+    save.has("replay_orders")
+  }
+
+  pub fn push(&mut self, turn: i32, order: Order) {
+    self.orders.push(RecordedOrder { turn, order });
+  }
+
+  pub fn to_save(&self, world: &World) -> SaveFile {
+    let mut save = self.start.clone();
+    save.put("replay_orders", &self.orders);
+    save.put("replay_checksum", &world.checksum());
+    save
+  }
+
+  pub fn from_save(save: SaveFile) -> Result<Replay, SaveError> {
+    let orders = save.get("replay_orders")?;
+    let checksum = Some(save.get("replay_checksum")?);
+    Ok(Replay {
+      start: save,
+      orders,
+      checksum,
+    })
+  }
+}
+
+pub enum ReplayStatus {
+  Playing,
+  Verified(u32),
+  Diverged {
+    expected: u32,
+    actual: u32,
+  },
+}
+
+pub struct ReplayPlayer {
+  replay: Replay,
+  next_order: usize,
+}
+
+impl ReplayPlayer {
+  pub fn new(replay: Replay) -> Result<(ReplayPlayer, Box<World>), SaveError> {
+    let mut world = World::load(&replay.start)?;
+    simulate_vision_system(&mut world);
+    let player = ReplayPlayer {
+      replay,
+      next_order: 0,
+    };
+    Ok((player, world))
+  }
+
+  // Feeds the orders of the current phase into the world and simulates it,
+  // playing back a single phase per call.
+  pub fn step(&mut self, world: &mut World, counters: &mut Counters) -> ReplayStatus {
+    while let Some(recorded) = self.replay.orders.get(self.next_order) {
+      if recorded.turn > world.turn {
+        break;
+      }
+      self.next_order += 1;
+      simulate_order(world, recorded.order);
+      if recorded.order == Order::Commit {
+        break;
+      }
+    }
+    simulate_step_system(world, counters);
+    if self.next_order < self.replay.orders.len() {
+      return ReplayStatus::Playing;
+    }
+    let actual = world.checksum();
+    match self.replay.checksum {
+      Some(expected) if expected != actual => ReplayStatus::Diverged { expected, actual },
+      _ => ReplayStatus::Verified(actual),
+    }
+  }
+
+  // Hands the replay back so recording can continue from where it stopped.
+  pub fn into_replay(self) -> Replay {
+    let mut replay = self.replay;
+    replay.orders.truncate(self.next_order);
+    replay.checksum = None;
+    replay
+  }
+}
@@ -0,0 +1,387 @@
+use std::collections::{
+  BTreeMap,
+  BTreeSet,
+  VecDeque,
+};
+use crate::hex::{
+  Hex,
+  hex,
+};
+
+const SAVE_MAGIC: &str = "triplanetary-save";
+
+#[derive(Clone, Debug)]
+pub struct SaveError(pub String);
+
+impl std::fmt::Display for SaveError {
+  fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+    write!(f, "{}", self.0)
+  }
+}
+
+pub fn save_error<T>(message: impl Into<String>) -> Result<T, SaveError> {
+  Err(SaveError(message.into()))
+}
+
+// Upgrades a save file from version `n` to version `n + 1`, where `n` is the
+// index of the migration in the list handed to `SaveFile::parse`.
+pub type Migration = fn(&mut SaveFile) -> Result<(), SaveError>;
+
+pub trait Persist: Sized {
+  fn write(&self, w: &mut SaveWriter);
+  fn read(r: &mut SaveReader) -> Result<Self, SaveError>;
+}
+
+#[derive(Default)]
+pub struct SaveWriter {
+  out: String,
+}
+
+impl SaveWriter {
+  pub fn write<T: Persist>(&mut self, value: &T) {
+    value.write(self);
+  }
+
+  pub fn token(&mut self, token: &str) {
+    if !self.out.is_empty() && !self.out.ends_with('\n') {
+      self.out.push(' ');
+    }
+    self.out.push_str(token);
+  }
+
+  pub fn newline(&mut self) {
+    self.out.push('\n');
+  }
+
+  pub fn finish(self) -> String {
+    self.out
+  }
+}
+
+pub struct SaveReader<'a> {
+  input: &'a str,
+  position: usize,
+}
+
+impl<'a> SaveReader<'a> {
+  pub fn new(input: &'a str) -> Self {
+    Self {
+      input,
+      position: 0,
+    }
+  }
+
+  pub fn read<T: Persist>(&mut self) -> Result<T, SaveError> {
+    T::read(self)
+  }
+
+  pub fn token(&mut self) -> Result<&'a str, SaveError> {
+    let rest = &self.input[self.position..];
+    let start = rest.len() - rest.trim_start().len();
+    let rest = &rest[start..];
+    let length = rest.find(char::is_whitespace).unwrap_or(rest.len());
+    if length == 0 {
+      return save_error("unexpected end of section");
+    }
+    self.position += start + length;
+    Ok(&rest[..length])
+  }
+
+  pub fn parse<T: std::str::FromStr>(&mut self) -> Result<T, SaveError> {
+    let token = self.token()?;
+    match token.parse() {
+      Ok(x) => Ok(x),
+      Err(_) => save_error(format!("malformed value {token:?}")),
+    }
+  }
+
+  pub fn expect(&mut self, expected: &str) -> Result<(), SaveError> {
+    let token = self.token()?;
+    if token != expected {
+      return save_error(format!("expected {expected:?} but found {token:?}"));
+    }
+    Ok(())
+  }
+
+  fn raw(&mut self, length: usize) -> Result<&'a str, SaveError> {
+    let end = self.position + length;
+    match self.input.get(self.position..end) {
+      Some(x) => {
+        self.position = end;
+        Ok(x)
+      },
+      None => save_error("string runs past the end of section"),
+    }
+  }
+}
+
+#[derive(Clone)]
+pub struct SaveFile {
+  pub version: u32,
+  sections: BTreeMap<String, String>,
+}
+
+impl SaveFile {
+  pub fn new(version: u32) -> Self {
+    Self {
+      version,
+      sections: BTreeMap::new(),
+    }
+  }
+
+  pub fn parse(text: &str, migrations: &[Migration]) -> Result<Self, SaveError> {
+    let mut lines = text.lines();
+    let header = match lines.next() {
+      Some(x) => x,
+      None => return save_error("save file is empty"),
+    };
+    let version = match header.split_once(' ') {
+      Some((SAVE_MAGIC, version)) => match version.trim().parse::<u32>() {
+        Ok(x) => x,
+        Err(_) => return save_error(format!("malformed save version {version:?}")),
+      },
+      _ => return save_error("not a triplanetary save file"),
+    };
+    let mut save = SaveFile::new(version);
+    let mut current: Option<(String, String)> = None;
+    for line in lines {
+      if let Some(name) = line.strip_prefix('[').and_then(|x| x.strip_suffix(']')) {
+        if let Some((name, body)) = current.take() {
+          save.sections.insert(name, body);
+        }
+        current = Some((name.to_string(), String::new()));
+        continue;
+      }
+      match current {
+        Some((_, ref mut body)) => {
+          body.push_str(line);
+          body.push('\n');
+        },
+        None if line.trim().is_empty() => {},
+        None => return save_error("data found outside of a section"),
+      }
+    }
+    if let Some((name, body)) = current.take() {
+      save.sections.insert(name, body);
+    }
+    let current_version = migrations.len() as u32 + 1;
+    if save.version == 0 || save.version > current_version {
+      return save_error(format!("unsupported save version {}", save.version));
+    }
+    while save.version < current_version {
+      migrations[save.version as usize - 1](&mut save)?;
+      save.version += 1;
+    }
+    Ok(save)
+  }
+
+  pub fn has(&self, name: &str) -> bool {
+    self.sections.contains_key(name)
+  }
+
+  pub fn put<T: Persist>(&mut self, name: &str, value: &T) {
+    let mut w = SaveWriter::default();
+    w.write(value);
+    self.put_raw(name, w.finish());
+  }
+
+  pub fn get<T: Persist>(&self, name: &str) -> Result<T, SaveError> {
+    let body = match self.sections.get(name) {
+      Some(x) => x,
+      None => return save_error(format!("missing section [{name}]")),
+    };
+    let mut r = SaveReader::new(body);
+    match r.read() {
+      Ok(x) => Ok(x),
+      Err(SaveError(e)) => save_error(format!("in section [{name}]: {e}")),
+    }
+  }
+
+  pub fn get_or_default<T: Persist + Default>(&self, name: &str) -> Result<T, SaveError> {
+    if self.has(name) {
+      self.get(name)
+    } else {
+      Ok(T::default())
+    }
+  }
+
+  pub fn put_raw(&mut self, name: &str, mut body: String) {
+    if !body.ends_with('\n') {
+      body.push('\n');
+    }
+    self.sections.insert(name.to_string(), body);
+  }
+
+  pub fn get_raw(&self, name: &str) -> Option<&str> {
+    self.sections.get(name).map(|x| x.as_str())
+  }
+
+  pub fn remove(&mut self, name: &str) -> Option<String> {
+    self.sections.remove(name)
+  }
+
+  pub fn rename(&mut self, from: &str, to: &str) {
+    if let Some(body) = self.sections.remove(from) {
+      self.sections.insert(to.to_string(), body);
+    }
+  }
+
+  pub fn to_text(&self) -> String {
+    let mut output = format!("{SAVE_MAGIC} {}\n", self.version);
+    for (name, body) in self.sections.iter() {
+      output.push_str(&format!("[{name}]\n{body}"));
+    }
+    output
+  }
+}
+
+macro_rules! persist_from_str {
+  ($($t:ty),*) => {
+    $(
+      impl Persist for $t {
+        fn write(&self, w: &mut SaveWriter) {
+          w.token(&self.to_string());
+        }
+
+        fn read(r: &mut SaveReader) -> Result<Self, SaveError> {
+          r.parse()
+        }
+      }
+    )*
+  };
+}
+
+persist_from_str!(i32, u8, u16, u32, u64, usize, f32, bool);
+
+impl Persist for String {
+  fn write(&self, w: &mut SaveWriter) {
+    w.token(&format!("{}:{self}", self.len()));
+  }
+
+  fn read(r: &mut SaveReader) -> Result<Self, SaveError> {
+    let rest = &r.input[r.position..];
+    let start = rest.len() - rest.trim_start().len();
+    r.position += start;
+    let length = match r.input[r.position..].split_once(':') {
+      Some((length, _)) => match length.parse::<usize>() {
+        Ok(x) => {
+          r.position += length.len() + 1;
+          x
+        },
+        Err(_) => return save_error(format!("malformed string length {length:?}")),
+      },
+      None => return save_error("malformed string"),
+    };
+    Ok(r.raw(length)?.to_string())
+  }
+}
+
+impl Persist for Hex {
+  fn write(&self, w: &mut SaveWriter) {
+    w.write(&self.q);
+    w.write(&self.r);
+  }
+
+  fn read(r: &mut SaveReader) -> Result<Self, SaveError> {
+    Ok(hex(r.read()?, r.read()?))
+  }
+}
+
+impl<T: Persist> Persist for Option<T> {
+  fn write(&self, w: &mut SaveWriter) {
+    match self {
+      Some(x) => {
+        w.token("some");
+        w.write(x);
+      },
+      None => w.token("none"),
+    }
+  }
+
+  fn read(r: &mut SaveReader) -> Result<Self, SaveError> {
+    match r.token()? {
+      "some" => Ok(Some(r.read()?)),
+      "none" => Ok(None),
+      x => save_error(format!("expected option but found {x:?}")),
+    }
+  }
+}
+
+impl<A: Persist, B: Persist> Persist for (A, B) {
+  fn write(&self, w: &mut SaveWriter) {
+    w.write(&self.0);
+    w.write(&self.1);
+  }
+
+  fn read(r: &mut SaveReader) -> Result<Self, SaveError> {
+    Ok((r.read()?, r.read()?))
+  }
+}
+
+impl<T: Persist> Persist for Vec<T> {
+  fn write(&self, w: &mut SaveWriter) {
+    w.write(&self.len());
+    for x in self.iter() {
+      w.write(x);
+    }
+  }
+
+  fn read(r: &mut SaveReader) -> Result<Self, SaveError> {
+    let length: usize = r.read()?;
+    let mut results = Vec::with_capacity(length);
+    for _ in 0..length {
+      results.push(r.read()?);
+    }
+    Ok(results)
+  }
+}
+
+impl<T: Persist> Persist for VecDeque<T> {
+  fn write(&self, w: &mut SaveWriter) {
+    w.write(&self.len());
+    for x in self.iter() {
+      w.write(x);
+    }
+  }
+
+  fn read(r: &mut SaveReader) -> Result<Self, SaveError> {
+    let results: Vec<T> = r.read()?;
+    Ok(results.into_iter().collect())
+  }
+}
+
+impl<T: Persist + Ord> Persist for BTreeSet<T> {
+  fn write(&self, w: &mut SaveWriter) {
+    w.write(&self.len());
+    for x in self.iter() {
+      w.write(x);
+    }
+  }
+
+  fn read(r: &mut SaveReader) -> Result<Self, SaveError> {
+    let results: Vec<T> = r.read()?;
+    Ok(results.into_iter().collect())
+  }
+}
+
+impl<K: Persist + Ord, V: Persist> Persist for BTreeMap<K, V> {
+  fn write(&self, w: &mut SaveWriter) {
+    w.write(&self.len());
+    w.newline();
+    for (k, v) in self.iter() {
+      w.write(k);
+      w.write(v);
+      w.newline();
+    }
+  }
+
+  fn read(r: &mut SaveReader) -> Result<Self, SaveError> {
+    let length: usize = r.read()?;
+    let mut results = BTreeMap::new();
+    for _ in 0..length {
+      let k = r.read()?;
+      results.insert(k, r.read()?);
+    }
+    Ok(results)
+  }
+}
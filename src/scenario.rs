@@ -0,0 +1,108 @@
+use std::collections::VecDeque;
+use crate::ai::{
+  Ai,
+  Difficulty,
+};
+use crate::color::{
+  BLUE,
+  GRAY,
+  GREEN,
+  RED,
+  YELLOW,
+};
+use crate::faction::{
+  Faction,
+  FactionId,
+};
+use crate::hex::{
+  Hex,
+  hex,
+};
+use crate::ordnance::{
+  Magazine,
+  ship_class,
+};
+use crate::simulation::{
+  simulate_generate_around_anchors_system,
+  simulate_vision_system,
+};
+use crate::spawn::{
+  spawn_base,
+  spawn_planet,
+};
+use crate::world::{
+  CombatStrength,
+  DEFAULT_FUEL_CAPACITY,
+  Engine,
+  GravityStrength,
+  HistoryEvent,
+  ObjectType,
+  Sensor,
+  VisibleObject,
+  World,
+};
+
+pub fn initialize(world: &mut World, seed: u32) {
+  world.seed = seed;
+  let player_faction_id = FactionId(0);
+  let enemy_faction_id = FactionId(1);
+  world.factions.insert(player_faction_id, Faction::new("player", GREEN));
+  world.factions.insert(enemy_faction_id, Faction::new("enemy", RED));
+  let player_entity_id = world.entities.create();
+  let player_starting_position = hex(0, 0);
+  fn new_history(position: Hex) -> VecDeque<HistoryEvent> {
+    vec![
+      HistoryEvent {
+        position,
+        thrust_applied: 0,
+      }
+    ].into_iter().collect()
+  }
+  {
+    world.name.set(player_entity_id, "player".to_string());
+    world.position.set(player_entity_id, player_starting_position);
+    world.velocity.set(player_entity_id, hex(0, 0));
+    world.history.set(player_entity_id, new_history(player_starting_position));
+    world.visible_object.set(player_entity_id, VisibleObject(ObjectType::Ship, GREEN));
+    world.engine.set(player_entity_id, Engine {
+      power: 1,
+      thrust_applied: 0,
+      fuel: DEFAULT_FUEL_CAPACITY,
+      fuel_capacity: DEFAULT_FUEL_CAPACITY,
+    });
+    world.combat.set(player_entity_id, CombatStrength { attack: 2, defence: 2 });
+    world.ship_class.set(player_entity_id, "frigate".to_string());
+    world.magazine.set(player_entity_id, Magazine::full(ship_class("frigate").unwrap()));
+    world.sensor.set(player_entity_id, Sensor { range: 20, active: true });
+    world.owner.set(player_entity_id, player_faction_id);
+    world.player = Some(player_entity_id);
+  }
+  {
+    let enemy_starting_position = hex(-20, 20);
+    let entity_id = world.entities.create();
+    world.name.set(entity_id, "enemy".to_string());
+    world.position.set(entity_id, enemy_starting_position);
+    world.velocity.set(entity_id, hex(0, 0));
+    world.history.set(entity_id, new_history(enemy_starting_position));
+    world.visible_object.set(entity_id, VisibleObject(ObjectType::Ship, RED));
+    world.engine.set(entity_id, Engine {
+      power: 1,
+      thrust_applied: 0,
+      fuel: DEFAULT_FUEL_CAPACITY,
+      fuel_capacity: DEFAULT_FUEL_CAPACITY,
+    });
+    world.combat.set(entity_id, CombatStrength { attack: 2, defence: 2 });
+    world.ship_class.set(entity_id, "corvette".to_string());
+    world.magazine.set(entity_id, Magazine::full(ship_class("corvette").unwrap()));
+    world.sensor.set(entity_id, Sensor { range: 20, active: false });
+    world.owner.set(entity_id, enemy_faction_id);
+    world.ai.set(entity_id, Ai::new(Difficulty::Normal));
+  }
+  spawn_planet(world, "planet", hex(4, -1), 0, GravityStrength::Full, BLUE);
+  spawn_planet(world, "moon", hex(12, -9), 0, GravityStrength::Weak, GRAY);
+  let station_entity_id = spawn_base(world, "station", hex(-6, 3), YELLOW);
+  // the station is worth fighting over, so its neighbourhood stays loaded
+  world.anchor.set(station_entity_id, 10);
+  simulate_generate_around_anchors_system(world);
+  simulate_vision_system(world);
+}
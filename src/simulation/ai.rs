@@ -0,0 +1,217 @@
+use crate::entity::EntityId;
+use crate::faction::Relation;
+use crate::hex::{
+  Hex,
+  hex,
+};
+use crate::ordnance::OrdnanceKind;
+use crate::world::{
+  Nav,
+  ObjectType,
+  VisibleObject,
+  World,
+};
+use super::combat::{
+  CombatResult,
+  combat_modifier,
+  combat_result,
+  odds_column,
+};
+use super::orders::{
+  Order,
+  simulate_order,
+};
+
+// Targets closer than this are fought at matched course rather than chased.
+pub const ATTACK_RANGE: i32 = 4;
+
+// Something an AI ship can spend its turn on.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Intent {
+  Attack(EntityId),
+  Pursue(EntityId),
+  Refuel(EntityId),
+  Evade(EntityId),
+  Objective(Hex),
+  Idle,
+}
+
+impl Intent {
+  pub fn nav(self, world: &World) -> Option<Nav> {
+    Some(match self {
+      Intent::Attack(target_entity_id) => Nav::Rendezvous(target_entity_id),
+      Intent::Pursue(target_entity_id) => Nav::Seek(target_entity_id),
+      Intent::Refuel(base_entity_id) => Nav::GoTo(*world.position.get(base_entity_id)?),
+      Intent::Evade(threat_entity_id) => Nav::Flee(threat_entity_id),
+      Intent::Objective(position) => Nav::GoTo(position),
+      Intent::Idle => Nav::Idle,
+    })
+  }
+}
+
+// Scores everything the entity could do this turn from what its faction
+// knows. Ships and ordnance have to be identified; bases are charted and always
+// known, and only those of hostile factions are no use for refuelling.
+pub fn score_intents(world: &World, entity_id: EntityId) -> Vec<(Intent, f32)> {
+  let mut results = vec![(Intent::Idle, 0.)];
+  let ai = match world.ai.get(entity_id) {
+    Some(x) => *x,
+    None => return results,
+  };
+  let weights = ai.weights;
+  let position = match world.position.get(entity_id) {
+    Some(x) => *x,
+    None => return results,
+  };
+  let fuel = match world.engine.get(entity_id) {
+    Some(x) if x.fuel_capacity > 0 => x.fuel as f32 / x.fuel_capacity as f32,
+    _ => 0.,
+  };
+  let condition = if world.is_disabled(entity_id) { 0.25 } else { 1. };
+  for other in hostiles(world, entity_id) {
+    let distance = (*world.position.get(other).unwrap() - position).mag();
+    let advantage = advantage(world, entity_id, other) * condition;
+    if distance <= ATTACK_RANGE {
+      results.push((Intent::Attack(other), weights.attack * advantage));
+    }
+    let pursue = weights.pursue * advantage * fuel / (1. + distance as f32 / 10.);
+    results.push((Intent::Pursue(other), pursue));
+  }
+  for other in incoming_ordnance(world, entity_id) {
+    let distance = (*world.position.get(other).unwrap() - position).mag();
+    results.push((Intent::Evade(other), weights.evade * 2. / (1. + distance as f32)));
+  }
+  if fuel < 1. {
+    let nearest_base = world.entities.all()
+      .filter(|x| matches!(world.visible_object.get(*x), Some(VisibleObject(ObjectType::Base, _))))
+      .filter(|x| !world.owner.has(*x) || world.relation(entity_id, *x) == Relation::Friend)
+      .filter_map(|x| Some((x, (*world.position.get(x)? - position).mag())))
+      .min_by_key(|x| x.1);
+    if let Some((base_entity_id, distance)) = nearest_base {
+      let need = (1. - fuel) * (1. - fuel);
+      results.push((Intent::Refuel(base_entity_id), weights.refuel * need * 2. / (1. + distance as f32 / 20.)));
+    }
+  }
+  if let Some(objective) = ai.objective {
+    results.push((Intent::Objective(objective), weights.objective));
+  }
+  results
+}
+
+pub fn best_intent(world: &World, entity_id: EntityId) -> Intent {
+  // ties go to the option scored first
+  let mut best = (Intent::Idle, f32::MIN);
+  for (intent, score) in score_intents(world, entity_id) {
+    if score > best.1 {
+      best = (intent, score);
+    }
+  }
+  best.0
+}
+
+// Picks a nav goal for every AI ship before the nav computer plots burns.
+pub fn simulate_ai_astrogation_system(world: &mut World) {
+  for entity_id in world.entities.all() {
+    if !world.ai.has(entity_id) {
+      continue;
+    }
+    let nav = must_continue!(best_intent(world, entity_id).nav(world));
+    world.nav.set(entity_id, nav);
+  }
+}
+
+// Sends torpedoes at the closest hostile once it is within launch range.
+pub fn simulate_ai_ordnance_system(world: &mut World) {
+  for entity_id in world.entities.all() {
+    let ai = *must_continue!(world.ai.get(entity_id));
+    let position = *must_continue!(world.position.get(entity_id));
+    let course = must_continue!(world.plotted_course(entity_id));
+    let target_entity_id = must_continue!(hostiles(world, entity_id).into_iter()
+      .min_by_key(|x| (*world.position.get(*x).unwrap() - position).mag()));
+    let target_position = *must_continue!(world.position.get(target_entity_id));
+    if (target_position - position).mag() > ai.weights.launch_range {
+      continue;
+    }
+    // the target's burn for this turn is still secret, only its course is known
+    let target_course = world.course(target_entity_id).unwrap_or(hex(0, 0));
+    let boost = hex(0, 0).move_to(target_position + target_course - position - course, OrdnanceKind::Torpedo.boost());
+    simulate_order(world, Order::Launch(entity_id, OrdnanceKind::Torpedo, boost));
+  }
+}
+
+// Fires on the hostile with the best chance of a hit, if it is worth a shot.
+pub fn simulate_ai_combat_system(world: &mut World) {
+  for entity_id in world.entities.all() {
+    let ai = *must_continue!(world.ai.get(entity_id));
+    // the AI picks its own shots rather than firing at whatever it seeks
+    world.target.del(entity_id);
+    let attack = must_continue!(world.combat.get(entity_id)).attack;
+    let best = hostiles(world, entity_id).into_iter().filter_map(|other| {
+      let defence = world.combat.get(other)?.defence;
+      let modifier = combat_modifier(world, entity_id, other)?;
+      let odds = odds_column(attack, defence);
+      // a shot that misses even on a six is wasted
+      if odds < ai.weights.min_odds || combat_result(attack, defence, 6, modifier) == CombatResult::Miss {
+        return None;
+      }
+      Some((other, (odds as i32, modifier)))
+    }).max_by_key(|x| x.1);
+    if let Some((target_entity_id, _)) = best {
+      simulate_order(world, Order::Fire(entity_id, target_entity_id));
+    }
+  }
+}
+
+// Armed enemy ships the entity's faction has identified. Unidentified blips
+// could be anything and are left alone.
+pub fn hostiles(world: &World, entity_id: EntityId) -> Vec<EntityId> {
+  world.entities.all().filter(|other| {
+    if world.relation(entity_id, *other) != Relation::Foe || !world.combat.has(*other) {
+      return false;
+    }
+    if !matches!(world.visible_object.get(*other), Some(VisibleObject(ObjectType::Ship, _))) {
+      return false;
+    }
+    world.position.has(*other) && world.identifies(entity_id, *other)
+  }).collect()
+}
+
+// Identified enemy ordnance that could reach the entity next turn.
+fn incoming_ordnance(world: &World, entity_id: EntityId) -> Vec<EntityId> {
+  let position = match world.position.get(entity_id) {
+    Some(x) => *x,
+    None => return vec![],
+  };
+  let course = world.course(entity_id).unwrap_or(hex(0, 0));
+  world.entities.all().filter(|other| {
+    let ordnance = match world.ordnance.get(*other) {
+      Some(x) => x,
+      None => return false,
+    };
+    let (other_position, other_course) = match (world.position.get(*other), world.course(*other)) {
+      (Some(a), Some(b)) => (*a, b),
+      _ => return false,
+    };
+    let reach = other_course.mag() + 1;
+    ordnance.launcher != entity_id
+      && world.relation(entity_id, *other) == Relation::Foe
+      && world.identifies(entity_id, *other)
+      && (position + course - other_position).mag() <= reach
+  }).collect()
+}
+
+// How the entity's guns compare with the other's: above one it hits harder
+// than it gets hit.
+pub fn advantage(world: &World, entity_id: EntityId, other: EntityId) -> f32 {
+  let (ours, theirs) = match (world.combat.get(entity_id), world.combat.get(other)) {
+    (Some(a), Some(b)) => (*a, *b),
+    _ => return 0.,
+  };
+  let ours_column = odds_column(ours.attack, theirs.defence) as f32 + 1.;
+  let theirs_column = if world.is_disabled(other) {
+    0.
+  } else {
+    odds_column(theirs.attack, ours.defence) as f32
+  };
+  ours_column / (theirs_column + 1.)
+}
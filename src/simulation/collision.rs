@@ -0,0 +1,173 @@
+use crate::entity::EntityId;
+use crate::hex::{
+  Hex,
+  hex,
+};
+use crate::world::{
+  GravityStrength,
+  ObjectType,
+  VisibleObject,
+  World,
+};
+use super::combat::{
+  apply_combat_result,
+  other_damage_result,
+};
+use super::events::GameEvent;
+use super::ordnance::{
+  blast_targets,
+  ignores,
+  simulate_detonation_event,
+};
+
+// Asteroid hexes can be crossed safely at this speed or below.
+pub const ASTEROID_SAFE_SPEED: i32 = 1;
+
+// Ships and bases closing at this speed or below dock instead of ramming.
+pub const DOCKING_SPEED: i32 = 1;
+
+pub fn simulate_collision_event(world: &mut World, a: EntityId, b: EntityId) {
+  let VisibleObject(type_b, _) = match world.visible_object.get(b) {
+    Some(x) => *x,
+    None => return,
+  };
+  match type_b {
+    ObjectType::Ship => simulate_collision_contact(world, a, b),
+    ObjectType::Asteroid => simulate_collision_asteroid(world, a, b),
+    ObjectType::Planet => simulate_collision_planet(world, a, b),
+    ObjectType::Base => simulate_collision_contact(world, a, b),
+    ObjectType::Torpedo | ObjectType::Mine | ObjectType::Nuke => simulate_collision_ordnance(world, a, b),
+    ObjectType::Gravity0 => simulate_collision_gravity(world, 0, a),
+    ObjectType::Gravity1 => simulate_collision_gravity(world, 1, a),
+    ObjectType::Gravity2 => simulate_collision_gravity(world, 2, a),
+    ObjectType::Gravity3 => simulate_collision_gravity(world, 3, a),
+    ObjectType::Gravity4 => simulate_collision_gravity(world, 4, a),
+    ObjectType::Gravity5 => simulate_collision_gravity(world, 5, a),
+    ObjectType::WeakGravity0 => simulate_collision_weak_gravity(world, 0, a, b),
+    ObjectType::WeakGravity1 => simulate_collision_weak_gravity(world, 1, a, b),
+    ObjectType::WeakGravity2 => simulate_collision_weak_gravity(world, 2, a, b),
+    ObjectType::WeakGravity3 => simulate_collision_weak_gravity(world, 3, a, b),
+    ObjectType::WeakGravity4 => simulate_collision_weak_gravity(world, 4, a, b),
+    ObjectType::WeakGravity5 => simulate_collision_weak_gravity(world, 5, a, b),
+  }
+}
+
+// Every asteroid hex entered faster than `ASTEROID_SAFE_SPEED` risks damage.
+fn simulate_collision_asteroid(world: &mut World, entity_id: EntityId, asteroid_entity_id: EntityId) {
+  let velocity = *must_return!(world.velocity.get(entity_id));
+  let position = *must_return!(world.position.get(asteroid_entity_id));
+  if velocity.mag() <= ASTEROID_SAFE_SPEED || !world.combat.has(entity_id) {
+    return;
+  }
+  let roll = world.rng("hazard").roll(6);
+  let result = other_damage_result(roll, 0);
+  world.events.push(GameEvent::AsteroidHazard {
+    entity_id,
+    position,
+    roll,
+    result,
+  });
+  apply_combat_result(world, entity_id, result);
+}
+
+// Planets stop anything that runs into them; the mover is left in the last
+// open hex of its path with no velocity.
+fn simulate_collision_planet(world: &mut World, entity_id: EntityId, planet_entity_id: EntityId) {
+  let position = *must_return!(world.position.get(planet_entity_id));
+  world.events.push(GameEvent::Crashed {
+    entity_id,
+    position,
+  });
+  if world.velocity.has(entity_id) {
+    world.velocity.set(entity_id, hex(0, 0));
+  }
+  world.pending_gravity.del(entity_id);
+}
+
+// Ships and bases meeting at low relative speed dock, anything faster is a
+// ram that damages both sides, worse the harder they hit.
+fn simulate_collision_contact(world: &mut World, entity_id: EntityId, other: EntityId) {
+  let velocity = *must_return!(world.velocity.get(entity_id));
+  let other_velocity = world.course(other).unwrap_or(hex(0, 0));
+  let relative_speed = (velocity - other_velocity).mag();
+  if relative_speed <= DOCKING_SPEED {
+    world.events.push(GameEvent::Docked {
+      entity_id,
+      other,
+    });
+    return;
+  }
+  // the sides roll in order of their velocity, not of which was created first
+  let mut sides = [(entity_id, other), (other, entity_id)];
+  sides.sort_by_key(|(x, _)| world.velocity.get(*x).map(|v| (v.q, v.r)));
+  for (victim, cause) in sides {
+    if !world.combat.has(victim) {
+      continue;
+    }
+    let roll = world.rng("hazard").roll(6);
+    let result = other_damage_result(roll, relative_speed - DOCKING_SPEED - 1);
+    world.events.push(GameEvent::Rammed {
+      entity_id: victim,
+      other: cause,
+      relative_speed,
+      roll,
+      result,
+    });
+    apply_combat_result(world, victim, result);
+  }
+}
+
+// Running into ordnance sets it off with the mover caught in the blast.
+fn simulate_collision_ordnance(world: &mut World, entity_id: EntityId, ordnance_entity_id: EntityId) {
+  if ignores(world, ordnance_entity_id, entity_id) {
+    return;
+  }
+  let position = *must_return!(world.position.get(ordnance_entity_id));
+  let mut targets = blast_targets(world, ordnance_entity_id, position);
+  targets.push(entity_id);
+  simulate_detonation_event(world, ordnance_entity_id, &targets);
+}
+
+fn simulate_collision_gravity(world: &mut World, direction: i32, entity_id: EntityId) {
+  if !world.velocity.has(entity_id) {
+    return;
+  }
+  // gravity entered this turn only bends the course of the next move
+  let pending = match world.pending_gravity.get(entity_id) {
+    Some(x) => *x,
+    None => hex(0, 0),
+  };
+  world.pending_gravity.set(entity_id, pending + Hex::direction(direction));
+}
+
+fn simulate_collision_weak_gravity(world: &mut World, direction: i32, entity_id: EntityId, gravity_entity_id: EntityId) {
+  let position = match world.position.get(gravity_entity_id) {
+    Some(x) => *x,
+    None => return,
+  };
+  let mut choice = match world.gravity_choice.get(entity_id) {
+    Some(x) => *x,
+    None => Default::default(),
+  };
+  // a full well pulling on the same hex keeps the weak one from being ignored
+  let stacked = world.position.at(position).map(|x| x.iter().any(|other| {
+    let pull = world.visible_object.get(*other).and_then(|x| x.0.gravity_pull());
+    matches!(pull, Some((_, GravityStrength::Full)))
+  })).unwrap_or(false);
+  match choice.ignored {
+    None if choice.ignore_weak && !stacked => {
+      choice.ignored = Some((position, direction));
+      world.gravity_choice.set(entity_id, choice);
+      return;
+    },
+    Some((ignored_position, ignored_direction)) if (position - ignored_position).mag() == 1 => {
+      // two weak hexes in a row: the one that was skipped counts after all
+      simulate_collision_gravity(world, ignored_direction, entity_id);
+      choice.ignore_weak = false;
+      choice.ignored = None;
+      world.gravity_choice.set(entity_id, choice);
+    },
+    _ => {},
+  }
+  simulate_collision_gravity(world, direction, entity_id);
+}
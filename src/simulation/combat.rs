@@ -0,0 +1,131 @@
+use crate::entity::EntityId;
+use crate::world::World;
+use super::events::GameEvent;
+use super::vision::line_of_sight;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum CombatResult {
+  Miss,
+  Disabled(i32),
+  Eliminated,
+}
+
+use CombatResult::{
+  Disabled as D,
+  Eliminated as E,
+  Miss as M,
+};
+
+// A ship whose disabled turns add up past this is destroyed.
+pub const MAX_DISABLED_TURNS: i32 = 5;
+
+// Indexed by modified die roll (clamped to 0..=7) and then by odds column,
+// running from 1:4 up to 4:1.
+const COMBAT_RESULTS_TABLE: [[CombatResult; 6]; 8] = [
+  [M, M, M, M, M, M],
+  [M, M, M, M, M, D(2)],
+  [M, M, M, M, D(2), D(3)],
+  [M, M, M, D(2), D(3), D(4)],
+  [M, M, D(2), D(3), D(4), D(5)],
+  [M, D(2), D(3), D(4), D(5), E],
+  [D(2), D(3), D(4), D(5), E, E],
+  [D(3), D(4), D(5), E, E, E],
+];
+
+// Damage from anything other than gunfire, indexed by modified die roll
+// clamped to 0..=9.
+const OTHER_DAMAGE_TABLE: [CombatResult; 10] = [M, M, M, M, M, D(1), D(2), D(3), D(4), E];
+
+// Attack strength against defence rounded in the defender's favour:
+// 1:4, 1:2, 1:1, 2:1, 3:1 and 4:1.
+pub fn odds_column(attack: i32, defence: i32) -> usize {
+  if attack >= defence {
+    (1 + attack / defence.max(1)).min(5) as usize
+  } else if attack * 2 >= defence {
+    1
+  } else {
+    0
+  }
+}
+
+// Shots lose one point of die roll per hex of range past the first and per
+// hex of relative velocity past two.
+pub fn combat_modifier(world: &World, attacker: EntityId, target: EntityId) -> Option<i32> {
+  let range = (*world.position.get(target)? - *world.position.get(attacker)?).mag();
+  let relative_velocity = (world.course(target)? - world.course(attacker)?).mag();
+  Some(-(range - 1).max(0) - (relative_velocity - 2).max(0))
+}
+
+pub fn combat_result(attack: i32, defence: i32, roll: i32, modifier: i32) -> CombatResult {
+  let row = (roll + modifier).clamp(0, 7) as usize;
+  COMBAT_RESULTS_TABLE[row][odds_column(attack, defence)]
+}
+
+pub fn other_damage_result(roll: i32, modifier: i32) -> CombatResult {
+  OTHER_DAMAGE_TABLE[(roll + modifier).clamp(0, 9) as usize]
+}
+
+pub fn simulate_combat_system(world: &mut World) {
+  for entity_id in world.entities.all() {
+    if world.disabled.has(entity_id) && !world.is_disabled(entity_id) {
+      world.disabled.del(entity_id);
+    }
+  }
+  // every shot is rolled before any of them take effect, so ships knocked
+  // out this turn still get to fire back.
+  let mut results = vec![];
+  for entity_id in world.entities.all() {
+    let target_entity_id = *must_continue!(world.target.get(entity_id));
+    if world.is_disabled(entity_id) {
+      continue;
+    }
+    let attack = must_continue!(world.combat.get(entity_id)).attack;
+    let defence = must_continue!(world.combat.get(target_entity_id)).defence;
+    let from = *must_continue!(world.position.get(entity_id));
+    let to = *must_continue!(world.position.get(target_entity_id));
+    if attack <= 0 || !line_of_sight(world, from, to) {
+      continue;
+    }
+    let modifier = must_continue!(combat_modifier(world, entity_id, target_entity_id));
+    let roll = world.rng("combat").roll(6);
+    let result = combat_result(attack, defence, roll, modifier);
+    world.events.push(GameEvent::Fired {
+      entity_id,
+      target: target_entity_id,
+      roll,
+      modifier,
+      result,
+    });
+    results.push((target_entity_id, result));
+  }
+  for entity_id in world.entities.all() {
+    world.target.del(entity_id);
+  }
+  for (entity_id, result) in results {
+    apply_combat_result(world, entity_id, result);
+  }
+}
+
+// Disabled turns stack on top of any the entity is already serving and it is
+// destroyed once they add up past `MAX_DISABLED_TURNS`.
+pub fn apply_combat_result(world: &mut World, entity_id: EntityId, result: CombatResult) {
+  // an entity may be hit several times and already be gone
+  if !world.combat.has(entity_id) {
+    return;
+  }
+  // damage taken during this turn starts counting from the next one
+  let turns = match result {
+    CombatResult::Miss => return,
+    CombatResult::Disabled(x) => world.disabled_turns(entity_id).max(1) - 1 + x,
+    CombatResult::Eliminated => MAX_DISABLED_TURNS + 1,
+  };
+  if turns > MAX_DISABLED_TURNS {
+    world.events.push(GameEvent::Destroyed {
+      entity_id,
+      name: world.display_name(entity_id),
+    });
+    world.remove(entity_id);
+  } else {
+    world.disabled.set(entity_id, world.turn + turns);
+  }
+}
@@ -0,0 +1,60 @@
+use crate::entity::EntityId;
+use crate::hex::Hex;
+use crate::world::Phase;
+use super::combat::CombatResult;
+
+// What happened during the last simulated turn, kept on the world for the
+// frontend to show. Events are not saved.
+#[derive(Clone, Debug, PartialEq)]
+pub enum GameEvent {
+  PhaseChanged {
+    turn: i32,
+    from: Phase,
+    to: Phase,
+  },
+  AsteroidHazard {
+    entity_id: EntityId,
+    position: Hex,
+    roll: i32,
+    result: CombatResult,
+  },
+  Rammed {
+    entity_id: EntityId,
+    other: EntityId,
+    relative_speed: i32,
+    roll: i32,
+    result: CombatResult,
+  },
+  NoIntercept {
+    entity_id: EntityId,
+    target: EntityId,
+  },
+  Docked {
+    entity_id: EntityId,
+    other: EntityId,
+  },
+  Crashed {
+    entity_id: EntityId,
+    position: Hex,
+  },
+  Fired {
+    entity_id: EntityId,
+    target: EntityId,
+    roll: i32,
+    modifier: i32,
+    result: CombatResult,
+  },
+  Detonated {
+    entity_id: EntityId,
+    position: Hex,
+  },
+  CaughtInBlast {
+    entity_id: EntityId,
+    roll: Option<i32>,
+    result: CombatResult,
+  },
+  Destroyed {
+    entity_id: EntityId,
+    name: String,
+  },
+}
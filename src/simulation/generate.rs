@@ -0,0 +1,83 @@
+use std::collections::BTreeSet;
+use crate::delta::{
+  CHUNK_SIZE,
+  chunk_of,
+};
+use crate::hex::Hex;
+use crate::world::{
+  ObjectType,
+  VisibleObject,
+  World,
+};
+
+// Streams the procedural world in chunks around every load anchor. Chunks
+// coming into range are generated, with any recorded changes applied, and
+// generated entities left in chunks out of range are unloaded. Nothing else
+// is ever unloaded, so ships and ordnance far from the anchors keep flying.
+pub fn simulate_generate_around_anchors_system(world: &mut World) {
+  let anchors = load_anchors(world);
+  if anchors.is_empty() {
+    return;
+  }
+  let mut in_range = BTreeSet::new();
+  for (position, radius) in anchors {
+    // every chunk overlapping the box around the anchor's range
+    let (q0, r0) = chunk_of(position - Hex::new(radius, radius));
+    let (q1, r1) = chunk_of(position + Hex::new(radius, radius));
+    for q in q0..=q1 {
+      for r in r0..=r1 {
+        in_range.insert((q, r));
+      }
+    }
+  }
+  for entity_id in world.entities.all() {
+    if !world.generated.has(entity_id) {
+      continue;
+    }
+    let position = *must_continue!(world.position.get(entity_id));
+    if !in_range.contains(&chunk_of(position)) {
+      world.unload(entity_id);
+    }
+  }
+  for chunk in in_range.iter() {
+    if world.loaded_chunks.contains(chunk) {
+      continue;
+    }
+    for q in chunk.0 * CHUNK_SIZE..(chunk.0 + 1) * CHUNK_SIZE {
+      for r in chunk.1 * CHUNK_SIZE..(chunk.1 + 1) * CHUNK_SIZE {
+        let position = Hex::new(q, r);
+        if let Some(entities_at) = world.position.at(position) {
+          if !entities_at.is_empty() {
+            continue;
+          }
+        }
+        if let Some(visible_object) = world.generated_object(position) {
+          let entity_id = world.entities.create();
+          world.position.set(entity_id, position);
+          world.visible_object.set(entity_id, visible_object);
+          world.generated.set(entity_id, position);
+        }
+      }
+    }
+  }
+  world.loaded_chunks = in_range;
+}
+
+// Where the world is loaded around, and how far: the player's ships at sight
+// range, and anything given an anchor of its own such as scenario objectives.
+pub fn load_anchors(world: &World) -> Vec<(Hex, i32)> {
+  let player_faction_id = world.player_faction();
+  world.entities.all().filter_map(|entity_id| {
+    let position = *world.position.get(entity_id)?;
+    if let Some(radius) = world.anchor.get(entity_id) {
+      return Some((position, *radius));
+    }
+    let is_ship = matches!(world.visible_object.get(entity_id), Some(VisibleObject(ObjectType::Ship, _)));
+    let is_player_ship = is_ship && player_faction_id.is_some() && world.owner.get(entity_id) == player_faction_id.as_ref();
+    if world.player == Some(entity_id) || is_player_ship {
+      Some((position, world.vision_radius))
+    } else {
+      None
+    }
+  }).collect()
+}
@@ -0,0 +1,125 @@
+use crate::entity::EntityId;
+use crate::hex::{
+  Hex,
+  Path,
+  hex,
+};
+use crate::world::World;
+use super::collision::DOCKING_SPEED;
+
+// How many turns ahead the intercept solver looks before giving up.
+pub const INTERCEPT_MAX_TURNS: i32 = 24;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Intercept {
+  // Fly this course now to meet the target in `turns` turns.
+  Found {
+    course: Hex,
+    turns: i32,
+  },
+  Unreachable,
+}
+
+// Where something coasting from `position` on `course` will be at the end of
+// each of the next `turns` turns, bending its course with the gravity it
+// crosses the same way movement does.
+pub fn predict_drift(world: &World, position: Hex, course: Hex, turns: i32) -> Vec<Hex> {
+  let mut results = vec![];
+  let mut position = position;
+  let mut course = course;
+  for _ in 0..turns {
+    let (_, pull) = predict_move(world, position, course);
+    position = position + course;
+    course = course + pull;
+    results.push(position);
+  }
+  results
+}
+
+// The hexes a single move on `course` enters, picking between two equally
+// close hexes the way movement does, and the gravity they add to the next
+// course.
+pub fn predict_move(world: &World, position: Hex, course: Hex) -> (Vec<Hex>, Hex) {
+  let mut entered = vec![];
+  let mut pull = hex(0, 0);
+  for step in position.line(position + course).iter().skip(1) {
+    let a = match *step {
+      Path::One(a) => a,
+      Path::Alt(a, b) => {
+        if world.position.at(a).map(|x| x.is_empty()).unwrap_or(true) {
+          a
+        } else {
+          b
+        }
+      },
+    };
+    if let Some((direction, _)) = world.gravity_pull_at(a) {
+      pull = pull + Hex::direction(direction);
+    }
+    entered.push(a);
+  }
+  (entered, pull)
+}
+
+// Furthest a ship can shift its end position from its drift in `turns` turns
+// of burning `thrust` every turn: a burn now counts for every turn after it.
+fn reach(thrust: i32, turns: i32) -> i32 {
+  thrust * turns * (turns + 1) / 2
+}
+
+// Finds the earliest turn the entity can meet the target within
+// `INTERCEPT_MAX_TURNS` and the course to fly this turn to get there. With
+// `rendezvous` the entity also has to be able to match the target's course on
+// arrival instead of ramming it.
+pub fn solve_intercept(
+  world: &World,
+  entity_id: EntityId,
+  target_entity_id: EntityId,
+  thrust_limit: i32,
+  rendezvous: bool,
+) -> Option<Intercept> {
+  let position = *world.position.get(entity_id)?;
+  let course = world.course(entity_id)?;
+  let fuel = world.engine.get(entity_id)?.fuel;
+  let target_position = *world.position.get(target_entity_id)?;
+  let target_course = world.course(target_entity_id).unwrap_or(hex(0, 0));
+  let target_path = predict_drift(world, target_position, target_course, INTERCEPT_MAX_TURNS);
+  let mut candidates = vec![course];
+  candidates.extend(course.spiral(thrust_limit));
+  for (index, meeting_position) in target_path.iter().copied().enumerate() {
+    let turns = index as i32 + 1;
+    let offset = meeting_position - (position + course * turns);
+    if offset.mag() > reach(thrust_limit, turns) {
+      continue;
+    }
+    // burning early is worth `turns` hexes per unit of fuel, so this is the
+    // least fuel the shift can possibly take.
+    if (offset.mag() + turns - 1) / turns > fuel {
+      continue;
+    }
+    let arrival_course = match target_path.get(index + 1) {
+      Some(x) => *x - meeting_position,
+      None => target_course,
+    };
+    // pick this turn's course so that what is left stays reachable, and try
+    // a later meeting if no course does.
+    let best = candidates.iter().copied().map(|new_course| {
+      let remaining_turns = turns - 1;
+      let remaining = meeting_position - (position + new_course * turns);
+      let mut misses = (remaining.mag() > reach(thrust_limit, remaining_turns)) as i32;
+      let mut mismatch = 0;
+      if rendezvous {
+        mismatch = (arrival_course - new_course).mag();
+        misses += (mismatch > thrust_limit * remaining_turns + DOCKING_SPEED) as i32;
+      }
+      ((misses, remaining.mag(), mismatch, (new_course - course).mag()), new_course)
+    }).min_by_key(|x| x.0)?;
+    if best.0.0 == 0 {
+      return Some(Intercept::Found {
+        course: best.1,
+        turns,
+      });
+    }
+  }
+  Some(Intercept::Unreachable)
+}
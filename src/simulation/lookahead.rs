@@ -0,0 +1,162 @@
+use crate::ai::{
+  Ai,
+  Difficulty,
+  Lookahead,
+};
+use crate::component::Components;
+use crate::counters::Counters;
+use crate::entity::EntityId;
+use crate::hex::{
+  Hex,
+  hex,
+};
+use crate::world::{
+  Phase,
+  World,
+};
+use super::ai::{
+  advantage,
+  hostiles,
+};
+use super::{
+  simulate_event,
+  simulate_turn,
+};
+
+// Replaces the plotted burn of every entity with a lookahead budget by the one
+// that played out best.
+pub fn simulate_lookahead_system(world: &mut World) {
+  for entity_id in world.entities.all() {
+    let lookahead = *must_continue!(world.lookahead.get(entity_id));
+    let thrust = must_continue!(choose_burn(world, entity_id, lookahead));
+    world.plot.set(entity_id, thrust);
+  }
+}
+
+// Tries every burn the entity could make this turn by playing it forward on
+// snapshots of the world, with everyone flying as their AI would afterwards,
+// and returns the one with the best average outcome. Rollouts are handed out
+// to the burns in turn until the budget runs out. The budget counts rollouts
+// rather than time so every machine makes the same choice and replays stay
+// exact.
+pub fn choose_burn(world: &World, entity_id: EntityId, lookahead: Lookahead) -> Option<Hex> {
+  let engine = world.engine.get(entity_id)?;
+  let thrust_limit = world.thrust_limit(entity_id)?
+    .min(world.free_thrust(entity_id) + engine.power);
+  let mut candidates = vec![hex(0, 0)];
+  candidates.extend(hex(0, 0).spiral(thrust_limit));
+  let mut totals = vec![(0., 0); candidates.len()];
+  for rollout in 0..lookahead.rollouts.max(1) as usize {
+    let index = rollout % candidates.len();
+    let value = rollout_value(world, entity_id, candidates[index], lookahead.depth, rollout as u32);
+    totals[index].0 += value;
+    totals[index].1 += 1;
+  }
+  // ties go to the smaller burn, which comes first
+  let mut best = (hex(0, 0), f32::MIN);
+  for (thrust, (total, count)) in candidates.into_iter().zip(totals) {
+    if count == 0 {
+      continue;
+    }
+    let average = total / count as f32;
+    if average > best.1 {
+      best = (thrust, average);
+    }
+  }
+  Some(best.0)
+}
+
+// Plays `depth` turns forward from the given burn and scores where the
+// entity ends up.
+pub fn rollout_value(world: &World, entity_id: EntityId, thrust: Hex, depth: i32, salt: u32) -> f32 {
+  let center = world.position.get(entity_id).copied().unwrap_or(hex(0, 0));
+  let mut snapshot = world.snapshot(center, reach(world, entity_id, thrust, depth));
+  snapshot.rng_streams = world.rng_streams.fork(salt);
+  // the entity can only plan around the ships and ordnance its side has
+  // identified, anything else is left out of the rollout
+  for other in world.entities.all() {
+    let is_craft = world.combat.has(other) || world.ordnance.has(other);
+    if is_craft && !world.identifies(entity_id, other) {
+      snapshot.remove(other);
+    }
+  }
+  // nobody searches inside a search, and opponents without an AI are assumed
+  // to fight like a normal one
+  snapshot.lookahead = Components::default();
+  for other in hostiles(world, entity_id) {
+    if !snapshot.ai.has(other) {
+      snapshot.ai.set(other, Ai::new(Difficulty::Normal));
+    }
+  }
+  let ai = snapshot.ai.get(entity_id).copied();
+  let nav = snapshot.nav.get(entity_id).cloned();
+  snapshot.ai.del(entity_id);
+  snapshot.nav.del(entity_id);
+  // burns the others have plotted this turn are secret until they are made
+  snapshot.plot = Components::default();
+  snapshot.plot.set(entity_id, thrust);
+  let mut counters = Counters::default();
+  simulate_event(&mut snapshot, &mut counters);
+  if snapshot.position.has(entity_id) {
+    if let Some(ai) = ai {
+      snapshot.ai.set(entity_id, ai);
+    }
+    if let Some(nav) = nav {
+      snapshot.nav.set(entity_id, nav);
+    }
+  }
+  while snapshot.phase != Phase::Astrogation {
+    simulate_event(&mut snapshot, &mut counters);
+  }
+  for _ in 1..depth {
+    if !snapshot.position.has(entity_id) {
+      break;
+    }
+    simulate_turn(&mut snapshot, &mut counters);
+  }
+  evaluate(world, &snapshot, entity_id)
+}
+
+// How far from where it is now the entity could get in `depth` turns,
+// speeding up as hard as it can and pulled along by a gravity well each turn.
+// Terrain beyond that is left out of its rollouts.
+fn reach(world: &World, entity_id: EntityId, thrust: Hex, depth: i32) -> i32 {
+  let speed = world.velocity.get(entity_id).map(|x| x.mag()).unwrap_or(0) + thrust.mag();
+  let power = world.engine.get(entity_id).map(|x| x.power).unwrap_or(0);
+  depth * (speed + 1) + power * depth * (depth - 1) / 2 + 1
+}
+
+// Higher is better: staying alive and undamaged, hurting whoever was in
+// sight at the start, keeping fuel, and closing in when the odds favour it
+// or the objective when there is one.
+fn evaluate(before: &World, after: &World, entity_id: EntityId) -> f32 {
+  let position = match after.position.get(entity_id) {
+    Some(x) => *x,
+    None => return -1000.,
+  };
+  let mut value = -10. * after.disabled_turns(entity_id) as f32;
+  if let Some(engine) = after.engine.get(entity_id) {
+    value += 0.5 * engine.fuel as f32;
+  }
+  let mut nearest = None;
+  for other in hostiles(before, entity_id) {
+    match after.position.get(other) {
+      Some(x) => {
+        value += 10. * after.disabled_turns(other) as f32;
+        let distance = (*x - position).mag();
+        if nearest.map(|(_, d)| distance < d).unwrap_or(true) {
+          nearest = Some((other, distance));
+        }
+      },
+      None => value += 300.,
+    }
+  }
+  let objective = after.ai.get(entity_id).and_then(|x| x.objective);
+  if let Some(objective) = objective {
+    value -= (objective - position).mag() as f32;
+  } else if let Some((other, distance)) = nearest {
+    let approach = if advantage(before, entity_id, other) >= 1. { 1. } else { -1. };
+    value -= 0.5 * approach * distance as f32;
+  }
+  value
+}
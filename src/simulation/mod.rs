@@ -0,0 +1,170 @@
+mod ai;
+pub use ai::{
+  ATTACK_RANGE,
+  Intent,
+  best_intent,
+  score_intents,
+  simulate_ai_astrogation_system,
+  simulate_ai_combat_system,
+  simulate_ai_ordnance_system,
+};
+
+mod collision;
+pub use collision::{
+  ASTEROID_SAFE_SPEED,
+  DOCKING_SPEED,
+  simulate_collision_event,
+};
+
+mod combat;
+pub use combat::{
+  CombatResult,
+  MAX_DISABLED_TURNS,
+  apply_combat_result,
+  combat_modifier,
+  combat_result,
+  odds_column,
+  other_damage_result,
+  simulate_combat_system,
+};
+
+mod events;
+pub use events::{
+  GameEvent,
+};
+
+mod generate;
+pub use generate::{
+  load_anchors,
+  simulate_generate_around_anchors_system,
+};
+
+mod intercept;
+pub use intercept::{
+  INTERCEPT_MAX_TURNS,
+  Intercept,
+  predict_drift,
+  predict_move,
+  solve_intercept,
+};
+
+mod lookahead;
+pub use lookahead::{
+  choose_burn,
+  rollout_value,
+  simulate_lookahead_system,
+};
+
+mod movement;
+pub use movement::{
+  simulate_burn_system,
+  simulate_movement_system,
+};
+
+mod ordnance;
+pub use ordnance::{
+  blast_targets,
+  simulate_detonation_event,
+  simulate_launch_system,
+  simulate_ordnance_expiry_system,
+};
+
+mod orders;
+pub use orders::{
+  Order,
+  simulate_order,
+};
+
+mod nav;
+pub use nav::{
+  ESCORT_RADIUS,
+  simulate_nav_system,
+};
+
+mod pathfinding;
+pub use pathfinding::{
+  PLAN_MAX_NODES,
+  PLAN_MAX_TURNS,
+  plan_course,
+};
+
+mod refuel;
+pub use refuel::{
+  simulate_refuel_system,
+};
+
+mod vision;
+pub use vision::{
+  THRUST_DETECTION_FACTOR,
+  line_of_sight,
+  simulate_vision_system,
+};
+
+use crate::counters::Counters;
+use crate::world::{
+  Phase,
+  World,
+};
+
+pub fn simulate_step_system(world: &mut World, counters: &mut Counters) {
+  if !world.need_simulate {
+    return;
+  }
+  simulate_event(world, counters);
+  world.need_simulate = false;
+}
+
+// Runs the rest of the current turn, committing every phase on the way, and
+// stops at the next astrogation.
+pub fn simulate_turn(world: &mut World, counters: &mut Counters) {
+  loop {
+    simulate_event(world, counters);
+    if world.phase == Phase::Astrogation {
+      break;
+    }
+  }
+}
+
+// Resolves the end of the current phase and moves on, running straight
+// through the phases that take no orders.
+pub fn simulate_event(world: &mut World, counters: &mut Counters) {
+  loop {
+    match world.phase {
+      Phase::Astrogation => {
+        world.events.clear();
+        instrument!(counters, simulate_ai_astrogation_system(world));
+        instrument!(counters, simulate_nav_system(world));
+        instrument!(counters, simulate_lookahead_system(world));
+      },
+      Phase::Ordnance => {
+        instrument!(counters, simulate_ai_ordnance_system(world));
+        instrument!(counters, simulate_launch_system(world));
+      },
+      Phase::Movement => {
+        instrument!(counters, simulate_burn_system(world));
+        instrument!(counters, simulate_movement_system(world));
+        instrument!(counters, simulate_generate_around_anchors_system(world));
+        instrument!(counters, simulate_vision_system(world));
+      },
+      Phase::Combat => {
+        instrument!(counters, simulate_ai_combat_system(world));
+        instrument!(counters, simulate_combat_system(world));
+      },
+      Phase::Logistics => {
+        instrument!(counters, simulate_refuel_system(world));
+        instrument!(counters, simulate_ordnance_expiry_system(world));
+        world.turn += 1;
+      },
+    }
+    let from = world.phase;
+    world.phase = from.next();
+    world.events.push(GameEvent::PhaseChanged {
+      turn: world.turn,
+      from,
+      to: world.phase,
+    });
+    if world.phase.takes_orders() {
+      break;
+    }
+  }
+}
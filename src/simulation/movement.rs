@@ -0,0 +1,202 @@
+use std::collections::BTreeSet;
+use crate::entity::EntityId;
+use crate::hex::{
+  Hex,
+  Path,
+  hex,
+};
+use crate::world::{
+  HistoryEvent,
+  World,
+};
+use super::collision::simulate_collision_event;
+use super::ordnance::{
+  blast_targets,
+  simulate_detonation_event,
+};
+
+// Turns the burns plotted during astrogation into velocity, spending fuel.
+// The thrust stays on the engine until the next burn so that sensors can pick
+// up ships that have just fired their drives.
+pub fn simulate_burn_system(world: &mut World) {
+  for entity_id in world.entities.all() {
+    if let Some(engine) = world.engine.get_mut(entity_id) {
+      engine.thrust_applied = 0;
+    }
+    let thrust = *must_continue!(world.plot.get(entity_id));
+    world.plot.del(entity_id);
+    let thrust_limit = must_continue!(world.thrust_limit(entity_id));
+    if thrust.mag() > thrust_limit {
+      continue;
+    }
+    let fuel_cost = must_continue!(world.fuel_cost(entity_id, thrust.mag()));
+    let velocity = *must_continue!(world.velocity.get(entity_id));
+    let engine = must_continue!(world.engine.get_mut(entity_id));
+    engine.thrust_applied = thrust.mag();
+    engine.fuel -= fuel_cost;
+    world.velocity.set(entity_id, velocity + thrust);
+  }
+}
+
+struct Mover {
+  entity_id: EntityId,
+  start_position: Hex,
+  path: Vec<Path>,
+  orbiting: Option<EntityId>,
+  stopped: bool,
+}
+
+// Everything moves at once: a mover with speed `s` enters the `i`th hex of
+// its line at time `i / s`, and all entries that happen at the same time are
+// made before any of their collisions are resolved. The outcome does not
+// depend on the order entities were created in.
+pub fn simulate_movement_system(world: &mut World) {
+  let mut movers = vec![];
+  for entity_id in world.entities.all() {
+    let start_position = *must_continue!(world.position.get(entity_id));
+    let mut velocity = must_continue!(world.course(entity_id));
+    if world.landed.has(entity_id) {
+      // landed ships rest on the surface and only feel gravity once they
+      // take off, which any burn at all does.
+      velocity = *world.velocity.get(entity_id).unwrap();
+      if velocity != hex(0, 0) {
+        world.landed.del(entity_id);
+      }
+    }
+    world.velocity.set(entity_id, velocity);
+    world.pending_gravity.del(entity_id);
+    movers.push(Mover {
+      entity_id,
+      start_position,
+      path: start_position.line(start_position + velocity),
+      orbiting: world.orbit.get(entity_id).copied(),
+      stopped: false,
+    });
+  }
+  let mut steps = vec![];
+  for (index, mover) in movers.iter().enumerate() {
+    let speed = mover.path.len() - 1;
+    for step in 1..=speed {
+      steps.push((step, speed, index));
+    }
+  }
+  // order by time, comparing the fractions without rounding
+  steps.sort_by(|a, b| (a.0 * b.1).cmp(&(b.0 * a.1)).then(a.2.cmp(&b.2)));
+  let mut start = 0;
+  while start < steps.len() {
+    let (step, speed, _) = steps[start];
+    let end = start + steps[start..].iter()
+      .take_while(|x| x.0 * speed == step * x.1)
+      .count();
+    simulate_movement_substep(world, &mut movers, &steps[start..end]);
+    start = end;
+  }
+  for mover in movers.iter() {
+    let entity_id = mover.entity_id;
+    let end_position = must_continue!(world.position.get(entity_id).copied());
+    let velocity = *must_continue!(world.velocity.get(entity_id));
+    world.gravity_choice.del(entity_id);
+    let start_body = world.gravity_body_at(mover.start_position);
+    let end_body = world.gravity_body_at(end_position);
+    match (start_body, end_body) {
+      (Some(a), Some(b)) if a == b && velocity.mag() == 1 && !world.landed.has(entity_id) => {
+        world.orbit.set(entity_id, a);
+      },
+      _ => world.orbit.del(entity_id),
+    }
+    if let Some(ref mut history) = world.history.get_mut(entity_id) {
+      if let Some(engine) = world.engine.get(entity_id) {
+        if let Some(entry) = history.back_mut() {
+          entry.thrust_applied = engine.thrust_applied;
+        }
+      }
+      history.push_back(HistoryEvent {
+        position: end_position,
+        thrust_applied: 0,
+      });
+      if history.len() > 6 {
+        history.pop_front();
+      }
+    }
+  }
+}
+
+fn simulate_movement_substep(world: &mut World, movers: &mut [Mover], steps: &[(usize, usize, usize)]) {
+  // pick every destination against the same snapshot of the board before
+  // anyone moves.
+  let mut entries = vec![];
+  for &(step, _, index) in steps {
+    let mover = &movers[index];
+    if mover.stopped || !world.position.has(mover.entity_id) {
+      continue;
+    }
+    let a = match mover.path[step] {
+      Path::One(a) => a,
+      Path::Alt(a, b) => {
+        if is_occupied(world, a) {
+          b
+        } else {
+          a
+        }
+      },
+    };
+    entries.push((index, a));
+  }
+  let mut arrived = vec![];
+  for (index, a) in entries {
+    let entity_id = movers[index].entity_id;
+    if let Some(body) = world.surface_body_at(a) {
+      movers[index].stopped = true;
+      if movers[index].orbiting == Some(body) && world.velocity.get(entity_id).map(|x| x.mag()) == Some(1) {
+        // dropping out of orbit onto the planet below lands the ship on the
+        // hex side it came from.
+        world.landed.set(entity_id, body);
+        world.velocity.set(entity_id, hex(0, 0));
+        world.pending_gravity.del(entity_id);
+        continue;
+      }
+      // crashing leaves the mover in the last open hex of its path
+      for other in world.position.at(a).cloned().unwrap_or_default() {
+        simulate_collision_event(world, entity_id, other);
+      }
+      continue;
+    }
+    world.position.set(entity_id, a);
+    arrived.push(index);
+  }
+  let mut handled = BTreeSet::new();
+  for index in arrived {
+    let entity_id = movers[index].entity_id;
+    let a = match world.position.get(entity_id) {
+      Some(x) => *x,
+      None => continue,
+    };
+    if world.ordnance.has(entity_id) {
+      let targets = blast_targets(world, entity_id, a);
+      if !targets.is_empty() {
+        simulate_detonation_event(world, entity_id, &targets);
+        continue;
+      }
+    }
+    for other in world.position.at(a).cloned().unwrap_or_default() {
+      if other == entity_id || !world.position.has(other) {
+        continue;
+      }
+      // two movers arriving together only meet once
+      if !handled.insert((entity_id.min(other), entity_id.max(other))) {
+        continue;
+      }
+      simulate_collision_event(world, entity_id, other);
+      if !world.position.has(entity_id) {
+        break;
+      }
+    }
+  }
+}
+
+fn is_occupied(world: &World, position: Hex) -> bool {
+  match world.position.at(position) {
+    Some(x) => !x.is_empty(),
+    None => false,
+  }
+}
@@ -0,0 +1,184 @@
+use crate::entity::EntityId;
+use crate::hex::{
+  Hex,
+  Path,
+  hex,
+};
+use crate::world::{
+  Nav,
+  World,
+};
+use super::events::GameEvent;
+use super::intercept::{
+  Intercept,
+  solve_intercept,
+};
+use super::pathfinding::plan_course;
+
+// How close an escort keeps to the ship it protects.
+pub const ESCORT_RADIUS: i32 = 2;
+
+// How many turns ahead a fleeing ship compares its options.
+const FLEE_LOOKAHEAD: i32 = 3;
+
+pub fn simulate_nav_system(world: &mut World) {
+  for entity_id in world.entities.all() {
+    let nav = must_continue!(world.nav.get(entity_id)).clone();
+    let position = *must_continue!(world.position.get(entity_id));
+    let course = must_continue!(world.course(entity_id));
+    let engine = *must_continue!(world.engine.get(entity_id));
+    // the nav computer never overloads the engine
+    let thrust_limit = must_continue!(world.thrust_limit(entity_id))
+      .min(world.free_thrust(entity_id) + engine.power);
+    let desired_velocity = match nav {
+      Nav::Idle => {
+        hex(0, 0)
+      },
+      Nav::GoTo(target_position) => {
+        plan_go_to(world, position, course, target_position, thrust_limit, engine.fuel)
+      },
+      Nav::Patrol(mut waypoints) => {
+        if waypoints.first() == Some(&position) {
+          waypoints.rotate_left(1);
+          world.nav.set(entity_id, Nav::Patrol(waypoints.clone()));
+        }
+        match waypoints.first() {
+          Some(&x) => plan_go_to(world, position, course, x, thrust_limit, engine.fuel),
+          None => hex(0, 0),
+        }
+      },
+      Nav::Seek(target_entity_id) | Nav::Rendezvous(target_entity_id) => {
+        let rendezvous = matches!(nav, Nav::Rendezvous(_));
+        if world.combat.has(entity_id) && !rendezvous {
+          world.target.set(entity_id, target_entity_id);
+        }
+        match must_continue!(solve_intercept(world, entity_id, target_entity_id, thrust_limit, rendezvous)) {
+          Intercept::Found { course, .. } => course,
+          Intercept::Unreachable => {
+            // keep pace with the target until it comes within reach
+            world.events.push(GameEvent::NoIntercept {
+              entity_id,
+              target: target_entity_id,
+            });
+            world.course(target_entity_id).unwrap_or(course)
+          },
+        }
+      },
+      Nav::Follow(leader_entity_id, offset) => {
+        let leader_position = *must_continue!(world.position.get(leader_entity_id));
+        let leader_course = world.course(leader_entity_id).unwrap_or(hex(0, 0));
+        follow(position, course, leader_position + offset, leader_course)
+      },
+      Nav::Flee(threat_entity_id) => {
+        must_continue!(flee(world, entity_id, threat_entity_id, thrust_limit))
+      },
+      Nav::Escort(protected_entity_id) => {
+        let protected_position = *must_continue!(world.position.get(protected_entity_id));
+        let protected_course = world.course(protected_entity_id).unwrap_or(hex(0, 0));
+        // judge the distance at the end of the move, when both have drifted
+        let distance = (position + course - protected_position - protected_course).mag();
+        if distance <= ESCORT_RADIUS {
+          protected_course
+        } else {
+          follow(position, course, protected_position, protected_course)
+        }
+      },
+      Nav::Orbit(body_entity_id) => {
+        must_continue!(orbit(world, entity_id, body_entity_id, thrust_limit))
+      },
+      Nav::Land(body_entity_id) => {
+        if world.landed.get(entity_id) == Some(&body_entity_id) {
+          hex(0, 0)
+        } else if world.orbit.get(entity_id) == Some(&body_entity_id) {
+          // burn straight down onto the closest surface hex
+          let center = *must_continue!(world.position.get(body_entity_id));
+          hex(0, 0).move_to(center - position, 1)
+        } else {
+          must_continue!(orbit(world, entity_id, body_entity_id, thrust_limit))
+        }
+      },
+    };
+    // only speed up while there is fuel left to stop again
+    let mut new_course = course.move_to(desired_velocity, thrust_limit);
+    let fuel_cost = world.fuel_cost(entity_id, (new_course - course).mag()).unwrap();
+    if new_course.mag() > course.mag() && engine.fuel - fuel_cost < new_course.mag() {
+      new_course = course;
+    }
+    world.plot.set(entity_id, new_course - course);
+  }
+}
+
+// Plans a way around whatever is in the way, falling back to heading straight
+// for the target when no plan is found.
+fn plan_go_to(world: &World, position: Hex, course: Hex, target_position: Hex, thrust_limit: i32, fuel: i32) -> Hex {
+  match plan_course(world, position, course, target_position, thrust_limit, fuel) {
+    Some(x) => x,
+    None => go_to(position, course, target_position),
+  }
+}
+
+fn go_to(position: Hex, course: Hex, target_position: Hex) -> Hex {
+  let target_vector = target_position - position;
+  let target_length = target_vector.mag() as f32;
+  let desired_speed = target_length.sqrt().floor() as usize;
+  match hex(0, 0).line(target_vector).get(desired_speed) {
+    Some(&Path::One(x)) => x,
+    Some(&Path::Alt(x, _)) => x,
+    None => course,
+  }
+}
+
+// Like go_to but in the leader's frame, so the entity comes to rest relative
+// to it at the station.
+fn follow(position: Hex, course: Hex, station: Hex, leader_course: Hex) -> Hex {
+  leader_course + go_to(position, course - leader_course, station)
+}
+
+// Picks the course that leaves the entity furthest from where the threat will
+// be after a few turns of drifting, without steering into a planet.
+fn flee(world: &World, entity_id: EntityId, threat_entity_id: EntityId, thrust_limit: i32) -> Option<Hex> {
+  let position = *world.position.get(entity_id)?;
+  let course = world.course(entity_id)?;
+  let threat_position = *world.position.get(threat_entity_id)?;
+  let threat_course = world.course(threat_entity_id).unwrap_or(hex(0, 0));
+  let threat_end = threat_position + threat_course * FLEE_LOOKAHEAD;
+  let mut candidates = vec![course];
+  candidates.extend(course.spiral(thrust_limit));
+  candidates.into_iter()
+    .filter(|x| world.surface_body_at(position + *x).is_none())
+    .max_by_key(|x| {
+      let distance = (position + *x * FLEE_LOOKAHEAD - threat_end).mag();
+      (distance, -(*x - course).mag())
+    })
+}
+
+// Picks the course that gets the entity circling the planet: once in orbit
+// gravity does the rest, inside the gravity ring it looks for a speed one
+// course to the next ring hex, and from further out it heads for the ring.
+fn orbit(world: &World, entity_id: EntityId, body_entity_id: EntityId, thrust_limit: i32) -> Option<Hex> {
+  let position = *world.position.get(entity_id)?;
+  let course = world.course(entity_id)?;
+  let center = *world.position.get(body_entity_id)?;
+  let radius = world.planet.get(body_entity_id)?.radius;
+  if world.orbit.get(entity_id) == Some(&body_entity_id) {
+    return Some(course);
+  }
+  if world.landed.get(entity_id) == Some(&body_entity_id) {
+    // take off straight up, away from the planet
+    return Some(hex(0, 0).move_to(position - center, 1) * 2);
+  }
+  let in_ring = |x: Hex| world.gravity_body_at(x) == Some(body_entity_id);
+  if in_ring(position) {
+    let mut candidates = vec![course];
+    candidates.extend(course.spiral(thrust_limit));
+    let best = candidates.into_iter()
+      .filter(|x| x.mag() == 1 && in_ring(position + *x))
+      .min_by_key(|x| (*x - course).mag());
+    if let Some(x) = best {
+      return Some(x);
+    }
+  }
+  let closest_ring_hex = center.ring(radius + 1).into_iter()
+    .min_by_key(|x| (*x - position).mag())?;
+  Some(go_to(position, course, closest_ring_hex))
+}
@@ -0,0 +1,181 @@
+use crate::entity::EntityId;
+use crate::hex::Hex;
+use crate::save::{
+  Persist,
+  SaveError,
+  SaveReader,
+  SaveWriter,
+  save_error,
+};
+use crate::ordnance::{
+  OrdnanceKind,
+  ship_class,
+};
+use crate::world::{
+  GravityChoice,
+  Phase,
+  World,
+};
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Order {
+  // Plot a burn so that the entity arrives at the given hex at the end of the
+  // turn. Plotting again replaces the earlier burn.
+  Thrust(EntityId, Hex),
+  // Choose whether the next move skips the first weak gravity hex it can.
+  IgnoreWeakGravity(EntityId, bool),
+  // Fire on the target during this turn's combat.
+  Fire(EntityId, EntityId),
+  // Launch ordnance this turn, pushed off the launcher's course by the boost.
+  Launch(EntityId, OrdnanceKind, Hex),
+  // Switch the entity's sensor between active and passive.
+  Sensors(EntityId, bool),
+  Commit,
+}
+
+impl Order {
+  pub fn allowed_in(self, phase: Phase) -> bool {
+    match self {
+      Order::Thrust(..) | Order::IgnoreWeakGravity(..) | Order::Sensors(..) => phase == Phase::Astrogation,
+      Order::Launch(..) => phase == Phase::Ordnance,
+      Order::Fire(..) => phase == Phase::Combat,
+      Order::Commit => phase.takes_orders(),
+    }
+  }
+}
+
+impl Persist for Order {
+  fn write(&self, w: &mut SaveWriter) {
+    match self {
+      Order::Thrust(entity_id, destination) => {
+        w.token("Thrust");
+        w.write(entity_id);
+        w.write(destination);
+      },
+      Order::IgnoreWeakGravity(entity_id, ignore) => {
+        w.token("IgnoreWeakGravity");
+        w.write(entity_id);
+        w.write(ignore);
+      },
+      Order::Fire(entity_id, target_entity_id) => {
+        w.token("Fire");
+        w.write(entity_id);
+        w.write(target_entity_id);
+      },
+      Order::Launch(entity_id, kind, boost) => {
+        w.token("Launch");
+        w.write(entity_id);
+        w.write(kind);
+        w.write(boost);
+      },
+      Order::Sensors(entity_id, active) => {
+        w.token("Sensors");
+        w.write(entity_id);
+        w.write(active);
+      },
+      Order::Commit => w.token("Commit"),
+    }
+  }
+
+  fn read(r: &mut SaveReader) -> Result<Self, SaveError> {
+    match r.token()? {
+      "Thrust" => Ok(Order::Thrust(r.read()?, r.read()?)),
+      "IgnoreWeakGravity" => Ok(Order::IgnoreWeakGravity(r.read()?, r.read()?)),
+      "Fire" => Ok(Order::Fire(r.read()?, r.read()?)),
+      "Launch" => Ok(Order::Launch(r.read()?, r.read()?, r.read()?)),
+      "Sensors" => Ok(Order::Sensors(r.read()?, r.read()?)),
+      "Commit" => Ok(Order::Commit),
+      x => save_error(format!("unknown order {x:?}")),
+    }
+  }
+}
+
+// Applies an order and reports whether it was legal; rejected orders leave
+// the world untouched and should not be recorded.
+pub fn simulate_order(world: &mut World, order: Order) -> bool {
+  if !order.allowed_in(world.phase) {
+    return false;
+  }
+  match order {
+    Order::Thrust(entity_id, destination) => {
+      let position = match world.position.get(entity_id) {
+        Some(x) => *x,
+        None => return false,
+      };
+      let course = match world.course(entity_id) {
+        Some(x) => x,
+        None => return false,
+      };
+      let thrust_limit = match world.thrust_limit(entity_id) {
+        Some(x) => x,
+        None => return false,
+      };
+      let next_position = position + course;
+      if (destination - next_position).mag() > thrust_limit {
+        return false;
+      }
+      world.plot.set(entity_id, destination - next_position);
+      true
+    },
+    Order::IgnoreWeakGravity(entity_id, ignore_weak) => {
+      if !world.velocity.has(entity_id) {
+        return false;
+      }
+      world.gravity_choice.set(entity_id, GravityChoice {
+        ignore_weak,
+        ignored: None,
+      });
+      true
+    },
+    Order::Fire(entity_id, target_entity_id) => {
+      if entity_id == target_entity_id || world.is_disabled(entity_id) {
+        return false;
+      }
+      if !world.combat.has(entity_id) || !world.combat.has(target_entity_id) {
+        return false;
+      }
+      // nobody can aim at something their side has not identified
+      if !world.identifies(entity_id, target_entity_id) {
+        return false;
+      }
+      world.target.set(entity_id, target_entity_id);
+      true
+    },
+    Order::Launch(entity_id, kind, boost) => {
+      if world.is_disabled(entity_id) || boost.mag() > kind.boost() {
+        return false;
+      }
+      let class = match world.ship_class.get(entity_id).and_then(|x| ship_class(x)) {
+        Some(x) => x,
+        None => return false,
+      };
+      let mut launches = world.launch.get(entity_id).cloned().unwrap_or_default();
+      if launches.len() as i32 >= class.launches_per_turn {
+        return false;
+      }
+      let magazine = match world.magazine.get_mut(entity_id) {
+        Some(x) => x,
+        None => return false,
+      };
+      if magazine.count(kind) <= 0 {
+        return false;
+      }
+      magazine.loaded.insert(kind, magazine.count(kind) - 1);
+      launches.push((kind, boost));
+      world.launch.set(entity_id, launches);
+      true
+    },
+    Order::Sensors(entity_id, active) => {
+      let sensor = match world.sensor.get_mut(entity_id) {
+        Some(x) => x,
+        None => return false,
+      };
+      sensor.active = active;
+      true
+    },
+    Order::Commit => {
+      world.need_simulate = true;
+      true
+    },
+  }
+}
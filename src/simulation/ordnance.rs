@@ -0,0 +1,120 @@
+use crate::entity::EntityId;
+use crate::hex::Hex;
+use crate::ordnance::ORDNANCE_LIFETIME;
+use crate::spawn::spawn_ordnance;
+use crate::world::{
+  ObjectType,
+  VisibleObject,
+  World,
+};
+use super::events::GameEvent;
+use super::combat::{
+  CombatResult,
+  apply_combat_result,
+  combat_result,
+};
+
+// Queued launches leave once the launcher's course for the turn is settled,
+// so the ordnance starts out moving alongside it.
+pub fn simulate_launch_system(world: &mut World) {
+  for entity_id in world.entities.all() {
+    let launches = must_continue!(world.launch.get(entity_id)).clone();
+    world.launch.del(entity_id);
+    for (kind, boost) in launches {
+      spawn_ordnance(world, entity_id, kind, boost);
+    }
+  }
+}
+
+pub fn simulate_ordnance_expiry_system(world: &mut World) {
+  for entity_id in world.entities.all() {
+    let ordnance = *must_continue!(world.ordnance.get(entity_id));
+    if world.turn - ordnance.launch_turn >= ORDNANCE_LIFETIME {
+      world.remove(entity_id);
+    }
+  }
+}
+
+// Everything in the hex that would set the ordnance off. Gravity does not, and
+// on the turn it is launched neither does its launcher or anything launched
+// alongside it.
+pub fn blast_targets(world: &World, ordnance_entity_id: EntityId, position: Hex) -> Vec<EntityId> {
+  let entities_at = match world.position.at(position) {
+    Some(x) => x,
+    None => return vec![],
+  };
+  entities_at.iter().copied().filter(|entity_id| {
+    *entity_id != ordnance_entity_id && !ignores(world, ordnance_entity_id, *entity_id)
+  }).filter(|entity_id| {
+    match world.visible_object.get(*entity_id) {
+      Some(VisibleObject(object_type, _)) => object_type.gravity_pull().is_none(),
+      None => false,
+    }
+  }).collect()
+}
+
+pub fn ignores(world: &World, ordnance_entity_id: EntityId, entity_id: EntityId) -> bool {
+  let ordnance = match world.ordnance.get(ordnance_entity_id) {
+    Some(x) => x,
+    None => return false,
+  };
+  if ordnance.launch_turn != world.turn {
+    return false;
+  }
+  if entity_id == ordnance.launcher {
+    return true;
+  }
+  match world.ordnance.get(entity_id) {
+    Some(x) => x.launcher == ordnance.launcher && x.launch_turn == ordnance.launch_turn,
+    None => false,
+  }
+}
+
+// Rolls damage against everything caught in the blast and removes the
+// ordnance. Nukes skip the roll and destroy ships, asteroids and ordnance.
+pub fn simulate_detonation_event(world: &mut World, ordnance_entity_id: EntityId, targets: &[EntityId]) {
+  let ordnance = *must_return!(world.ordnance.get(ordnance_entity_id));
+  let position = *must_return!(world.position.get(ordnance_entity_id));
+  world.events.push(GameEvent::Detonated {
+    entity_id: ordnance_entity_id,
+    position,
+  });
+  world.remove(ordnance_entity_id);
+  for target_entity_id in targets.iter().copied() {
+    let object_type = match world.visible_object.get(target_entity_id) {
+      Some(VisibleObject(x, _)) => *x,
+      None => continue,
+    };
+    match ordnance.kind.attack() {
+      Some(attack) => {
+        let defence = must_continue!(world.combat.get(target_entity_id)).defence;
+        let roll = world.rng("ordnance").roll(6);
+        let result = combat_result(attack, defence, roll, 0);
+        world.events.push(GameEvent::CaughtInBlast {
+          entity_id: target_entity_id,
+          roll: Some(roll),
+          result,
+        });
+        apply_combat_result(world, target_entity_id, result);
+      },
+      None => match object_type {
+        ObjectType::Ship => {
+          world.events.push(GameEvent::CaughtInBlast {
+            entity_id: target_entity_id,
+            roll: None,
+            result: CombatResult::Eliminated,
+          });
+          apply_combat_result(world, target_entity_id, CombatResult::Eliminated);
+        },
+        ObjectType::Asteroid | ObjectType::Torpedo | ObjectType::Mine | ObjectType::Nuke => {
+          world.events.push(GameEvent::Destroyed {
+            entity_id: target_entity_id,
+            name: world.display_name(target_entity_id),
+          });
+          world.remove(target_entity_id);
+        },
+        _ => {},
+      },
+    }
+  }
+}
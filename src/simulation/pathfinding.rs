@@ -0,0 +1,120 @@
+use std::cmp::Reverse;
+use std::collections::{
+  BinaryHeap,
+  HashSet,
+};
+use crate::hex::{
+  Hex,
+  hex,
+};
+use crate::world::{
+  ObjectType,
+  VisibleObject,
+  World,
+};
+use super::collision::ASTEROID_SAFE_SPEED;
+use super::intercept::predict_move;
+
+// How many turns ahead a course is planned before giving up.
+pub const PLAN_MAX_TURNS: i32 = 30;
+
+// How many states the planner may look at for a single plan.
+pub const PLAN_MAX_NODES: usize = 4000;
+
+struct Node {
+  position: Hex,
+  course: Hex,
+  turns: i32,
+  fuel: i32,
+  first_course: Option<Hex>,
+}
+
+// Searches the burns that bring an entity at `position`, drifting on
+// `course`, to rest on `target_position`: planets and fast asteroid crossings
+// are avoided and gravity bends the course on the way just as it would when
+// moving. Returns the course to fly this turn, or `None` when nothing was
+// found within the budget. Arriving means ending a move on the target slow
+// enough to stop dead with the next burn.
+pub fn plan_course(
+  world: &World,
+  position: Hex,
+  course: Hex,
+  target_position: Hex,
+  thrust_limit: i32,
+  fuel: i32,
+) -> Option<Hex> {
+  let mut nodes = vec![Node {
+    position,
+    course,
+    turns: 0,
+    fuel,
+    first_course: None,
+  }];
+  let mut open = BinaryHeap::new();
+  let mut visited = HashSet::new();
+  let estimate = estimate_turns(position, course, target_position, thrust_limit)?;
+  open.push(Reverse((estimate, 0, 0)));
+  while let Some(Reverse((_, _, index))) = open.pop() {
+    let (position, course, turns, fuel, first_course) = {
+      let node = &nodes[index];
+      (node.position, node.course, node.turns, node.fuel, node.first_course)
+    };
+    if position == target_position && course.mag() <= thrust_limit {
+      return Some(first_course.unwrap_or(hex(0, 0)));
+    }
+    if !visited.insert((position, course)) || turns >= PLAN_MAX_TURNS || nodes.len() >= PLAN_MAX_NODES {
+      continue;
+    }
+    let mut candidates = vec![course];
+    candidates.extend(course.spiral(thrust_limit));
+    for new_course in candidates {
+      let new_fuel = fuel - (new_course - course).mag();
+      if new_fuel < 0 {
+        continue;
+      }
+      let (entered, pull) = predict_move(world, position, new_course);
+      if entered.iter().any(|x| is_hazard(world, *x, new_course)) {
+        continue;
+      }
+      let new_position = position + new_course;
+      let next_course = new_course + pull;
+      let estimate = match estimate_turns(new_position, next_course, target_position, thrust_limit) {
+        Some(x) => x,
+        None => continue,
+      };
+      // prefer the fastest plan, then the one that saves the most fuel
+      open.push(Reverse((turns + 1 + estimate, fuel - new_fuel, nodes.len())));
+      nodes.push(Node {
+        position: new_position,
+        course: next_course,
+        turns: turns + 1,
+        fuel: new_fuel,
+        first_course: first_course.or(Some(new_course)),
+      });
+    }
+  }
+  None
+}
+
+// A lower bound on the turns left, ignoring gravity: the drift has to be
+// shifted onto the target and the course brought down to a stoppable speed.
+fn estimate_turns(position: Hex, course: Hex, target_position: Hex, thrust_limit: i32) -> Option<i32> {
+  (0..=PLAN_MAX_TURNS).find(|turns| {
+    let offset = target_position - (position + course * *turns);
+    let reach = thrust_limit * turns * (turns + 1) / 2;
+    offset.mag() <= reach && course.mag() <= thrust_limit * (turns + 1)
+  })
+}
+
+fn is_hazard(world: &World, position: Hex, course: Hex) -> bool {
+  if world.surface_body_at(position).is_some() {
+    return true;
+  }
+  let entity_ids = match world.position.at(position) {
+    Some(x) => x,
+    None => return false,
+  };
+  course.mag() > ASTEROID_SAFE_SPEED && entity_ids.iter().any(|entity_id| {
+    matches!(world.visible_object.get(*entity_id), Some(VisibleObject(ObjectType::Asteroid, _)))
+  })
+}
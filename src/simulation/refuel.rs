@@ -0,0 +1,16 @@
+use crate::world::World;
+
+// Tops up the tanks of everything landed on a planet or parked next to a base.
+pub fn simulate_refuel_system(world: &mut World) {
+  for entity_id in world.entities.all() {
+    let position = *must_continue!(world.position.get(entity_id));
+    let course = must_continue!(world.course(entity_id));
+    let landed = world.landed.has(entity_id);
+    let docked = course.mag() == 0 && world.base_near(position);
+    if !landed && !docked {
+      continue;
+    }
+    let engine = must_continue!(world.engine.get_mut(entity_id));
+    engine.fuel = engine.fuel_capacity;
+  }
+}
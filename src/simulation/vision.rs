@@ -0,0 +1,146 @@
+use std::collections::{
+  BTreeMap,
+  HashMap,
+  HashSet,
+};
+use crate::component::Positions;
+use crate::faction::{
+  Contact,
+  FactionId,
+  Sighting,
+};
+use crate::hex::{
+  Hex,
+  Path,
+};
+use crate::world::{
+  Sensor,
+  World,
+};
+
+// Ships with their drives lit or their sensors active are detected this many
+// times further out than ones coasting quietly.
+pub const THRUST_DETECTION_FACTOR: i32 = 2;
+
+// Works out what each faction's sensors pick up. Hexes within identify range
+// of any of its ships or bases are in sight, and what is in them is
+// remembered as the faction's last sighting there. Ships and ordnance
+// further out but within detection range are contacts: they stay
+// unidentified blips unless the faction already knew what they were the turn
+// before. Ordnance does not spot.
+pub fn simulate_vision_system(world: &mut World) {
+  let mut observers: BTreeMap<FactionId, Vec<(Hex, Sensor)>> = BTreeMap::new();
+  for entity_id in world.entities.all() {
+    let faction_id = *must_continue!(world.owner.get(entity_id));
+    let position = *must_continue!(world.position.get(entity_id));
+    if world.ordnance.has(entity_id) {
+      continue;
+    }
+    observers.entry(faction_id).or_default().push((position, world.sensor_of(entity_id)));
+  }
+  let mut results = BTreeMap::new();
+  for (faction_id, faction) in world.factions.iter() {
+    let observers = observers.get(faction_id).map(|x| x.as_slice()).unwrap_or(&[]);
+    let mut visible = HashSet::new();
+    for (origin, sensor) in observers.iter() {
+      visible.extend(visible_from(&world.position, *origin, sensor.identify_range()));
+    }
+    let mut contacts = BTreeMap::new();
+    for entity_id in world.entities.all() {
+      if world.owner.get(entity_id) == Some(faction_id) {
+        continue;
+      }
+      if !world.ordnance.has(entity_id) && !world.combat.has(entity_id) {
+        continue;
+      }
+      let position = *must_continue!(world.position.get(entity_id));
+      let conspicuous = world.engine.get(entity_id).map(|x| x.thrust_applied > 0).unwrap_or(false)
+        || world.sensor.get(entity_id).map(|x| x.active).unwrap_or(false);
+      let factor = if conspicuous { THRUST_DETECTION_FACTOR } else { 1 };
+      let detected = observers.iter().any(|(origin, sensor)| {
+        (position - *origin).mag() <= sensor.range * factor && is_clear_between(&world.position, *origin, position)
+      });
+      if !detected {
+        continue;
+      }
+      let identified = visible.contains(&position)
+        || faction.contacts.get(&entity_id).map(|x| x.identified).unwrap_or(false);
+      contacts.insert(entity_id, Contact {
+        position,
+        identified,
+      });
+    }
+    let sightings: Vec<_> = visible.iter()
+      .map(|x| (*x, sighting(world, *faction_id, *x)))
+      .collect();
+    results.insert(*faction_id, (visible, contacts, sightings));
+  }
+  for (faction_id, (visible, contacts, sightings)) in results {
+    let faction = world.factions.get_mut(&faction_id).unwrap();
+    faction.explored.extend(sightings);
+    faction.visible = visible;
+    faction.contacts = contacts;
+  }
+}
+
+// What the faction sees in a hex it has in sight, leaving out its own
+// entities since it always knows where those are.
+fn sighting(world: &World, faction_id: FactionId, position: Hex) -> Sighting {
+  let objects = world.position.at(position).into_iter().flatten()
+    .copied()
+    .filter(|x| world.owner.get(*x) != Some(&faction_id))
+    .filter_map(|x| world.visible_object.get(x).copied())
+    .collect();
+  Sighting {
+    turn: world.turn,
+    objects,
+  }
+}
+
+// Every hex within the radius that can be seen from the origin: a hex is
+// hidden when the step towards the origin is hidden or obstructed, and both
+// hexes of an ambiguous step have to be clear.
+fn visible_from(positions: &Positions, origin: Hex, radius: i32) -> Vec<Hex> {
+  let mut visibility = HashMap::new();
+  visibility.insert(origin, (true, false));
+  let mut results = vec![origin];
+  for position in origin.spiral(radius) {
+    let is_visible_through = |position| {
+      let (visible, obstructed) = *visibility.get(&position).unwrap();
+      visible && !obstructed
+    };
+    let visible = match position.line(origin).get(1) {
+      Some(&Path::One(a)) => is_visible_through(a),
+      Some(&Path::Alt(a, b)) => is_visible_through(a) && is_visible_through(b),
+      None => true,
+    };
+    visibility.insert(position, (visible, is_obstructed(positions, position)));
+    if visible {
+      results.push(position);
+    }
+  }
+  results
+}
+
+// Whether `to` can be seen from `from`, using the same rule as the vision
+// system: every hex in between must be empty, and both hexes of an ambiguous
+// step must be.
+pub fn line_of_sight(world: &World, from: Hex, to: Hex) -> bool {
+  (to - from).mag() <= world.vision_radius && is_clear_between(&world.position, from, to)
+}
+
+fn is_clear_between(positions: &Positions, from: Hex, to: Hex) -> bool {
+  let path = from.line(to);
+  let between = path.len().saturating_sub(2);
+  path.iter().skip(1).take(between).all(|step| match *step {
+    Path::One(a) => !is_obstructed(positions, a),
+    Path::Alt(a, b) => !is_obstructed(positions, a) && !is_obstructed(positions, b),
+  })
+}
+
+fn is_obstructed(positions: &Positions, position: Hex) -> bool {
+  match positions.at(position) {
+    Some(x) => !x.is_empty(),
+    None => false,
+  }
+}
@@ -0,0 +1,109 @@
+use crate::color::{
+  Color,
+  WHITE,
+};
+use crate::entity::EntityId;
+use crate::hex::Hex;
+use crate::ordnance::{
+  Ordnance,
+  OrdnanceKind,
+};
+use crate::world::{
+  GravityStrength,
+  HistoryEvent,
+  ObjectType,
+  Planet,
+  VisibleObject,
+  World,
+};
+
+// Drops ordnance into the launcher's hex on the launcher's plotted course plus
+// any boost it was given, in the launcher's colors.
+pub fn spawn_ordnance(
+  world: &mut World,
+  launcher: EntityId,
+  kind: OrdnanceKind,
+  boost: Hex,
+) -> Option<EntityId> {
+  let position = *world.position.get(launcher)?;
+  let course = world.plotted_course(launcher)?;
+  let VisibleObject(_, color) = *world.visible_object.get(launcher)?;
+  let entity_id = world.entities.create();
+  world.name.set(entity_id, format!("{} {}", world.display_name(launcher), kind.to_string().to_lowercase()));
+  world.position.set(entity_id, position);
+  world.velocity.set(entity_id, course + boost);
+  world.history.set(entity_id, vec![
+    HistoryEvent {
+      position,
+      thrust_applied: boost.mag(),
+    },
+  ].into_iter().collect());
+  world.visible_object.set(entity_id, VisibleObject(kind.object_type(), color));
+  if let Some(faction_id) = world.owner.get(launcher).copied() {
+    world.owner.set(entity_id, faction_id);
+  }
+  world.ordnance.set(entity_id, Ordnance {
+    kind,
+    launcher,
+    launch_turn: world.turn,
+  });
+  Some(entity_id)
+}
+
+pub fn spawn_base(world: &mut World, name: &str, position: Hex, color: Color) -> EntityId {
+  let entity_id = world.entities.create();
+  world.name.set(entity_id, name.to_string());
+  world.position.set(entity_id, position);
+  world.visible_object.set(entity_id, VisibleObject(ObjectType::Base, color));
+  entity_id
+}
+
+// Creates a planet of the given radius centered on `center`, with one surface
+// entity per hex it covers and a ring of gravity hexes around it that pull
+// towards its center. Returns the planet's body entity.
+pub fn spawn_planet(
+  world: &mut World,
+  name: &str,
+  center: Hex,
+  radius: i32,
+  gravity: GravityStrength,
+  color: Color,
+) -> EntityId {
+  let body_entity_id = world.entities.create();
+  world.name.set(body_entity_id, name.to_string());
+  world.position.set(body_entity_id, center);
+  world.visible_object.set(body_entity_id, VisibleObject(ObjectType::Planet, color));
+  world.planet.set(body_entity_id, Planet { radius, gravity });
+  world.body.set(body_entity_id, body_entity_id);
+  for position in center.spiral(radius) {
+    let entity_id = world.entities.create();
+    world.position.set(entity_id, position);
+    world.visible_object.set(entity_id, VisibleObject(ObjectType::Planet, color));
+    world.body.set(entity_id, body_entity_id);
+  }
+  for position in center.ring(radius + 1) {
+    let entity_id = world.entities.create();
+    world.position.set(entity_id, position);
+    let direction = inward_direction(position, center);
+    let object_type = match gravity {
+      GravityStrength::Full => ObjectType::gravity(direction),
+      GravityStrength::Weak => ObjectType::weak_gravity(direction),
+    };
+    world.visible_object.set(entity_id, VisibleObject(object_type, WHITE));
+    world.body.set(entity_id, body_entity_id);
+  }
+  body_entity_id
+}
+
+fn inward_direction(position: Hex, center: Hex) -> i32 {
+  let mut best = 0;
+  let mut best_distance = i32::MAX;
+  for direction in 0..6 {
+    let distance = (position + Hex::direction(direction) - center).mag();
+    if distance < best_distance {
+      best = direction;
+      best_distance = distance;
+    }
+  }
+  best
+}
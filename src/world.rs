@@ -0,0 +1,1040 @@
+use std::collections::{
+  BTreeMap,
+  BTreeSet,
+  VecDeque,
+};
+use crate::ai::{
+  Ai,
+  Lookahead,
+};
+use crate::color::Color;
+use crate::delta::WorldDeltas;
+use crate::faction::{
+  Contact,
+  Faction,
+  FactionId,
+  Relation,
+  Sighting,
+};
+use crate::component::{
+  Components,
+  Positions,
+};
+use crate::entity::{
+  EntityId,
+  EntityTracker,
+};
+use crate::hex::{
+  Hex,
+  hex,
+};
+use crate::math::{
+  Rng,
+  RngStreams,
+  fbm,
+  hash2_u32,
+  hash_bytes_u32,
+  remap,
+};
+use crate::ordnance::{
+  Magazine,
+  Ordnance,
+  OrdnanceKind,
+};
+use crate::simulation::{
+  GameEvent,
+  line_of_sight,
+};
+use crate::save::{
+  Migration,
+  Persist,
+  SaveError,
+  SaveFile,
+  SaveReader,
+  SaveWriter,
+  save_error,
+};
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ObjectType {
+  Ship,
+  Asteroid,
+  Planet,
+  Base,
+  Torpedo,
+  Mine,
+  Nuke,
+  Gravity0,
+  Gravity1,
+  Gravity2,
+  Gravity3,
+  Gravity4,
+  Gravity5,
+  WeakGravity0,
+  WeakGravity1,
+  WeakGravity2,
+  WeakGravity3,
+  WeakGravity4,
+  WeakGravity5,
+}
+
+impl ObjectType {
+  pub fn gravity(direction: i32) -> ObjectType {
+    match direction.rem_euclid(6) {
+      0 => ObjectType::Gravity0,
+      1 => ObjectType::Gravity1,
+      2 => ObjectType::Gravity2,
+      3 => ObjectType::Gravity3,
+      4 => ObjectType::Gravity4,
+      _ => ObjectType::Gravity5,
+    }
+  }
+
+  pub fn weak_gravity(direction: i32) -> ObjectType {
+    match direction.rem_euclid(6) {
+      0 => ObjectType::WeakGravity0,
+      1 => ObjectType::WeakGravity1,
+      2 => ObjectType::WeakGravity2,
+      3 => ObjectType::WeakGravity3,
+      4 => ObjectType::WeakGravity4,
+      _ => ObjectType::WeakGravity5,
+    }
+  }
+
+  pub fn gravity_pull(self) -> Option<(i32, GravityStrength)> {
+    match self {
+      ObjectType::Gravity0 => Some((0, GravityStrength::Full)),
+      ObjectType::Gravity1 => Some((1, GravityStrength::Full)),
+      ObjectType::Gravity2 => Some((2, GravityStrength::Full)),
+      ObjectType::Gravity3 => Some((3, GravityStrength::Full)),
+      ObjectType::Gravity4 => Some((4, GravityStrength::Full)),
+      ObjectType::Gravity5 => Some((5, GravityStrength::Full)),
+      ObjectType::WeakGravity0 => Some((0, GravityStrength::Weak)),
+      ObjectType::WeakGravity1 => Some((1, GravityStrength::Weak)),
+      ObjectType::WeakGravity2 => Some((2, GravityStrength::Weak)),
+      ObjectType::WeakGravity3 => Some((3, GravityStrength::Weak)),
+      ObjectType::WeakGravity4 => Some((4, GravityStrength::Weak)),
+      ObjectType::WeakGravity5 => Some((5, GravityStrength::Weak)),
+      _ => None,
+    }
+  }
+}
+
+impl std::fmt::Display for ObjectType {
+  fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+    write!(f, "{self:?}")
+  }
+}
+
+impl Persist for ObjectType {
+  fn write(&self, w: &mut SaveWriter) {
+    w.token(&self.to_string());
+  }
+
+  fn read(r: &mut SaveReader) -> Result<Self, SaveError> {
+    match r.token()? {
+      "Ship" => Ok(ObjectType::Ship),
+      "Asteroid" => Ok(ObjectType::Asteroid),
+      "Planet" => Ok(ObjectType::Planet),
+      "Base" => Ok(ObjectType::Base),
+      "Torpedo" => Ok(ObjectType::Torpedo),
+      "Mine" => Ok(ObjectType::Mine),
+      "Nuke" => Ok(ObjectType::Nuke),
+      "Gravity0" => Ok(ObjectType::Gravity0),
+      "Gravity1" => Ok(ObjectType::Gravity1),
+      "Gravity2" => Ok(ObjectType::Gravity2),
+      "Gravity3" => Ok(ObjectType::Gravity3),
+      "Gravity4" => Ok(ObjectType::Gravity4),
+      "Gravity5" => Ok(ObjectType::Gravity5),
+      "WeakGravity0" => Ok(ObjectType::WeakGravity0),
+      "WeakGravity1" => Ok(ObjectType::WeakGravity1),
+      "WeakGravity2" => Ok(ObjectType::WeakGravity2),
+      "WeakGravity3" => Ok(ObjectType::WeakGravity3),
+      "WeakGravity4" => Ok(ObjectType::WeakGravity4),
+      "WeakGravity5" => Ok(ObjectType::WeakGravity5),
+      x => save_error(format!("unknown object type {x:?}")),
+    }
+  }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct VisibleObject(pub ObjectType, pub Color);
+
+impl Persist for VisibleObject {
+  fn write(&self, w: &mut SaveWriter) {
+    w.write(&self.0);
+    w.write(&self.1);
+  }
+
+  fn read(r: &mut SaveReader) -> Result<Self, SaveError> {
+    Ok(VisibleObject(r.read()?, r.read()?))
+  }
+}
+
+#[derive(Clone, Debug)]
+pub enum Nav {
+  Idle,
+  GoTo(Hex),
+  // visits the waypoints in order, going back to the first after the last
+  Patrol(Vec<Hex>),
+  Seek(EntityId),
+  // like seek but matching the target's course on arrival instead of ramming
+  Rendezvous(EntityId),
+  // holds station at an offset from the leader, matching its course
+  Follow(EntityId, Hex),
+  Flee(EntityId),
+  // stays within `ESCORT_RADIUS` of the protected ship
+  Escort(EntityId),
+  Orbit(EntityId),
+  Land(EntityId),
+}
+
+impl Persist for Nav {
+  fn write(&self, w: &mut SaveWriter) {
+    match self {
+      Nav::Idle => w.token("Idle"),
+      Nav::GoTo(target_position) => {
+        w.token("GoTo");
+        w.write(target_position);
+      },
+      Nav::Patrol(waypoints) => {
+        w.token("Patrol");
+        w.write(waypoints);
+      },
+      Nav::Seek(target_entity_id) => {
+        w.token("Seek");
+        w.write(target_entity_id);
+      },
+      Nav::Rendezvous(target_entity_id) => {
+        w.token("Rendezvous");
+        w.write(target_entity_id);
+      },
+      Nav::Follow(leader_entity_id, offset) => {
+        w.token("Follow");
+        w.write(leader_entity_id);
+        w.write(offset);
+      },
+      Nav::Flee(threat_entity_id) => {
+        w.token("Flee");
+        w.write(threat_entity_id);
+      },
+      Nav::Escort(protected_entity_id) => {
+        w.token("Escort");
+        w.write(protected_entity_id);
+      },
+      Nav::Orbit(body_entity_id) => {
+        w.token("Orbit");
+        w.write(body_entity_id);
+      },
+      Nav::Land(body_entity_id) => {
+        w.token("Land");
+        w.write(body_entity_id);
+      },
+    }
+  }
+
+  fn read(r: &mut SaveReader) -> Result<Self, SaveError> {
+    match r.token()? {
+      "Idle" => Ok(Nav::Idle),
+      "GoTo" => Ok(Nav::GoTo(r.read()?)),
+      "Seek" => Ok(Nav::Seek(r.read()?)),
+      "Patrol" => Ok(Nav::Patrol(r.read()?)),
+      "Rendezvous" => Ok(Nav::Rendezvous(r.read()?)),
+      "Follow" => Ok(Nav::Follow(r.read()?, r.read()?)),
+      "Flee" => Ok(Nav::Flee(r.read()?)),
+      "Escort" => Ok(Nav::Escort(r.read()?)),
+      "Orbit" => Ok(Nav::Orbit(r.read()?)),
+      "Land" => Ok(Nav::Land(r.read()?)),
+      x => save_error(format!("unknown nav {x:?}")),
+    }
+  }
+}
+
+// The steps of a turn, in order. Orders are only taken in the phases they
+// belong to and the rest resolve on their own.
+#[derive(Copy, Clone, Debug, PartialEq, Default)]
+pub enum Phase {
+  // plot burns for the coming move
+  #[default]
+  Astrogation,
+  // launch torpedoes, mines and nukes
+  Ordnance,
+  // everything moves
+  Movement,
+  // ships fire on targets in sight
+  Combat,
+  // refuelling and upkeep before the next turn
+  Logistics,
+}
+
+impl Phase {
+  pub fn next(self) -> Phase {
+    match self {
+      Phase::Astrogation => Phase::Ordnance,
+      Phase::Ordnance => Phase::Movement,
+      Phase::Movement => Phase::Combat,
+      Phase::Combat => Phase::Logistics,
+      Phase::Logistics => Phase::Astrogation,
+    }
+  }
+
+  pub fn takes_orders(self) -> bool {
+    match self {
+      Phase::Astrogation | Phase::Ordnance | Phase::Combat => true,
+      Phase::Movement | Phase::Logistics => false,
+    }
+  }
+}
+
+impl std::fmt::Display for Phase {
+  fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+    write!(f, "{self:?}")
+  }
+}
+
+impl Persist for Phase {
+  fn write(&self, w: &mut SaveWriter) {
+    w.token(&self.to_string());
+  }
+
+  fn read(r: &mut SaveReader) -> Result<Self, SaveError> {
+    match r.token()? {
+      "Astrogation" => Ok(Phase::Astrogation),
+      "Ordnance" => Ok(Phase::Ordnance),
+      "Movement" => Ok(Phase::Movement),
+      "Combat" => Ok(Phase::Combat),
+      "Logistics" => Ok(Phase::Logistics),
+      x => save_error(format!("unknown phase {x:?}")),
+    }
+  }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum GravityStrength {
+  Full,
+  Weak,
+}
+
+impl Persist for GravityStrength {
+  fn write(&self, w: &mut SaveWriter) {
+    match self {
+      GravityStrength::Full => w.token("Full"),
+      GravityStrength::Weak => w.token("Weak"),
+    }
+  }
+
+  fn read(r: &mut SaveReader) -> Result<Self, SaveError> {
+    match r.token()? {
+      "Full" => Ok(GravityStrength::Full),
+      "Weak" => Ok(GravityStrength::Weak),
+      x => save_error(format!("unknown gravity strength {x:?}")),
+    }
+  }
+}
+
+// Lives on the entity at the center of a planet. Every surface and gravity
+// hex of the planet points back at that entity through `World::body`.
+#[derive(Copy, Clone, Debug)]
+pub struct Planet {
+  pub radius: i32,
+  pub gravity: GravityStrength,
+}
+
+impl Persist for Planet {
+  fn write(&self, w: &mut SaveWriter) {
+    w.write(&self.radius);
+    w.write(&self.gravity);
+  }
+
+  fn read(r: &mut SaveReader) -> Result<Self, SaveError> {
+    Ok(Planet {
+      radius: r.read()?,
+      gravity: r.read()?,
+    })
+  }
+}
+
+// A ship may let a single weak gravity hex pass without effect each move, as
+// long as it does not cross two of them in a row.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct GravityChoice {
+  pub ignore_weak: bool,
+  pub ignored: Option<(Hex, i32)>,
+}
+
+impl Persist for GravityChoice {
+  fn write(&self, w: &mut SaveWriter) {
+    w.write(&self.ignore_weak);
+    w.write(&self.ignored);
+  }
+
+  fn read(r: &mut SaveReader) -> Result<Self, SaveError> {
+    Ok(GravityChoice {
+      ignore_weak: r.read()?,
+      ignored: r.read()?,
+    })
+  }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct HistoryEvent {
+  pub position: Hex,
+  pub thrust_applied: i32,
+}
+
+impl Persist for HistoryEvent {
+  fn write(&self, w: &mut SaveWriter) {
+    w.write(&self.position);
+    w.write(&self.thrust_applied);
+  }
+
+  fn read(r: &mut SaveReader) -> Result<Self, SaveError> {
+    Ok(HistoryEvent {
+      position: r.read()?,
+      thrust_applied: r.read()?,
+    })
+  }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct Engine {
+  pub power: i32,
+  pub thrust_applied: i32,
+  pub fuel: i32,
+  pub fuel_capacity: i32,
+}
+
+impl Persist for Engine {
+  fn write(&self, w: &mut SaveWriter) {
+    w.write(&self.power);
+    w.write(&self.thrust_applied);
+    w.write(&self.fuel);
+    w.write(&self.fuel_capacity);
+  }
+
+  fn read(r: &mut SaveReader) -> Result<Self, SaveError> {
+    Ok(Engine {
+      power: r.read()?,
+      thrust_applied: r.read()?,
+      fuel: r.read()?,
+      fuel_capacity: r.read()?,
+    })
+  }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct CombatStrength {
+  pub attack: i32,
+  pub defence: i32,
+}
+
+impl Persist for CombatStrength {
+  fn write(&self, w: &mut SaveWriter) {
+    w.write(&self.attack);
+    w.write(&self.defence);
+  }
+
+  fn read(r: &mut SaveReader) -> Result<Self, SaveError> {
+    Ok(CombatStrength {
+      attack: r.read()?,
+      defence: r.read()?,
+    })
+  }
+}
+
+// What an entity can pick out around it. Anything within range and in line
+// of sight is detected; active sensors also identify it, passive ones only
+// identify at half range but do not give the ship away.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Sensor {
+  pub range: i32,
+  pub active: bool,
+}
+
+impl Sensor {
+  pub fn identify_range(self) -> i32 {
+    if self.active {
+      self.range
+    } else {
+      self.range / 2
+    }
+  }
+}
+
+impl Persist for Sensor {
+  fn write(&self, w: &mut SaveWriter) {
+    w.write(&self.range);
+    w.write(&self.active);
+  }
+
+  fn read(r: &mut SaveReader) -> Result<Self, SaveError> {
+    Ok(Sensor {
+      range: r.read()?,
+      active: r.read()?,
+    })
+  }
+}
+
+// Extra thrust a landed ship gets from the surface when taking off, enough to
+// climb out of the gravity hex it sits in.
+pub const TAKEOFF_BOOST: i32 = 1;
+
+// An engine can be pushed past its power by this much thrust, at a cost of
+// `OVERLOAD_FUEL_COST` fuel for every unit of overload.
+pub const OVERLOAD_THRUST: i32 = 1;
+pub const OVERLOAD_FUEL_COST: i32 = 2;
+
+pub const DEFAULT_FUEL_CAPACITY: i32 = 20;
+
+// Each entry upgrades a save from version `index + 1` to `index + 2`, so the
+// current save version is always one more than the number of migrations.
+pub const SAVE_MIGRATIONS: &[Migration] = &[
+  migrate_engine_fuel,
+  migrate_faction_contacts,
+  migrate_faction_sightings,
+];
+
+// Version 1 engines had no fuel, they are given a full default tank.
+fn migrate_engine_fuel(save: &mut SaveFile) -> Result<(), SaveError> {
+  let old: Components<(i32, i32)> = save.get_or_default("engine")?;
+  let mut engine = Components::default();
+  for (entity_id, (power, thrust_applied)) in old.iter() {
+    engine.set(entity_id, Engine {
+      power: *power,
+      thrust_applied: *thrust_applied,
+      fuel: DEFAULT_FUEL_CAPACITY,
+      fuel_capacity: DEFAULT_FUEL_CAPACITY,
+    });
+  }
+  save.put("engine", &engine);
+  Ok(())
+}
+
+// Version 2 factions had no contacts, they start out with none.
+fn migrate_faction_contacts(save: &mut SaveFile) -> Result<(), SaveError> {
+  type Old = ((String, Color), (Vec<FactionId>, Vec<(i32, i32)>));
+  let old: BTreeMap<FactionId, Old> = save.get_or_default("factions")?;
+  let factions: BTreeMap<_, _> = old.into_iter()
+    .map(|(faction_id, faction)| (faction_id, (faction, BTreeMap::<EntityId, Contact>::new())))
+    .collect();
+  save.put("factions", &factions);
+  Ok(())
+}
+
+// Version 3 factions only kept which hexes they had explored. What was seen
+// there is not known, so the hexes are remembered empty as of turn 0.
+fn migrate_faction_sightings(save: &mut SaveFile) -> Result<(), SaveError> {
+  type Old = (((String, Color), Vec<FactionId>), (Vec<(i32, i32)>, BTreeMap<EntityId, Contact>));
+  let old: BTreeMap<FactionId, Old> = save.get_or_default("factions")?;
+  let mut factions = BTreeMap::new();
+  for (faction_id, (((name, color), allies), (explored, contacts))) in old {
+    let mut faction = Faction::new(&name, color);
+    faction.allies = allies.into_iter().collect();
+    faction.contacts = contacts;
+    for (q, r) in explored {
+      faction.explored.insert(Hex::new(q, r), Sighting {
+        turn: 0,
+        objects: vec![],
+      });
+    }
+    factions.insert(faction_id, faction);
+  }
+  save.put("factions", &factions);
+  Ok(())
+}
+
+pub struct World {
+  pub seed: u32,
+  // changes made to the procedurally generated world
+  pub deltas: WorldDeltas,
+  // chunks whose procedural content is currently spawned
+  pub loaded_chunks: BTreeSet<(i32, i32)>,
+  pub rng_streams: RngStreams,
+  pub player: Option<EntityId>,
+  pub vision_radius: i32,
+  pub factions: BTreeMap<FactionId, Faction>,
+  pub events: Vec<GameEvent>,
+  pub need_simulate: bool,
+  pub turn: i32,
+  pub phase: Phase,
+  pub entities: EntityTracker,
+  pub name: Components<String>,
+  pub position: Positions,
+  pub velocity: Components<Hex>,
+  pub pending_gravity: Components<Hex>,
+  pub gravity_choice: Components<GravityChoice>,
+  pub history: Components<VecDeque<HistoryEvent>>,
+  pub engine: Components<Engine>,
+  pub sensor: Components<Sensor>,
+  pub visible_object: Components<VisibleObject>,
+  pub owner: Components<FactionId>,
+  // the hex a procedurally generated entity was spawned for
+  pub generated: Components<Hex>,
+  // how far around it the procedural world is kept loaded
+  pub anchor: Components<i32>,
+  pub nav: Components<Nav>,
+  pub ai: Components<Ai>,
+  pub lookahead: Components<Lookahead>,
+  pub planet: Components<Planet>,
+  pub body: Components<EntityId>,
+  pub orbit: Components<EntityId>,
+  pub landed: Components<EntityId>,
+  pub combat: Components<CombatStrength>,
+  pub target: Components<EntityId>,
+  pub disabled: Components<i32>,
+  pub ship_class: Components<String>,
+  pub magazine: Components<Magazine>,
+  pub ordnance: Components<Ordnance>,
+  pub launch: Components<Vec<(OrdnanceKind, Hex)>>,
+  pub plot: Components<Hex>,
+}
+
+impl World {
+  pub fn new() -> Box<Self> {
+    Box::new(Self {
+      seed: 0,
+      deltas: WorldDeltas::default(),
+      loaded_chunks: BTreeSet::new(),
+      rng_streams: RngStreams::default(),
+      player: None,
+      vision_radius: 50,
+      factions: BTreeMap::new(),
+      events: vec![],
+      need_simulate: false,
+      turn: 1,
+      phase: Phase::Astrogation,
+      entities: EntityTracker::default(),
+      name: Components::default(),
+      position: Positions::default(),
+      velocity: Components::default(),
+      pending_gravity: Components::default(),
+      gravity_choice: Components::default(),
+      history: Components::default(),
+      engine: Components::default(),
+      sensor: Components::default(),
+      visible_object: Components::default(),
+      owner: Components::default(),
+      generated: Components::default(),
+      anchor: Components::default(),
+      nav: Components::default(),
+      ai: Components::default(),
+      lookahead: Components::default(),
+      planet: Components::default(),
+      body: Components::default(),
+      orbit: Components::default(),
+      landed: Components::default(),
+      combat: Components::default(),
+      target: Components::default(),
+      disabled: Components::default(),
+      ship_class: Components::default(),
+      magazine: Components::default(),
+      ordnance: Components::default(),
+      launch: Components::default(),
+      plot: Components::default(),
+    })
+  }
+
+  // Removes the entity for good. Generated terrain stays gone when its hex is
+  // generated again.
+  pub fn remove(&mut self, entity_id: EntityId) {
+    if let Some(origin) = self.generated.get(entity_id).copied() {
+      self.deltas.set(origin, None);
+    }
+    self.delete(entity_id);
+  }
+
+  // Drops generated terrain that went out of range, recording wherever it
+  // now differs from what generation gives so it comes back as it was left.
+  pub fn unload(&mut self, entity_id: EntityId) {
+    if let Some(origin) = self.generated.get(entity_id).copied() {
+      let position = self.position.get(entity_id).copied();
+      let object = self.visible_object.get(entity_id).copied();
+      if position != Some(origin) {
+        self.deltas.set(origin, None);
+      }
+      if let Some(position) = position {
+        if self.generated_object(position) != object {
+          self.deltas.set(position, object);
+        }
+      }
+    }
+    self.delete(entity_id);
+  }
+
+  // What generation puts in the hex, once any changes to it are applied.
+  pub fn generated_object(&self, position: Hex) -> Option<VisibleObject> {
+    match self.deltas.get(position) {
+      Some(x) => x,
+      None => generate_object(self.seed, position),
+    }
+  }
+
+  fn delete(&mut self, entity_id: EntityId) {
+    self.name.del(entity_id);
+    self.position.del(entity_id);
+    self.velocity.del(entity_id);
+    self.pending_gravity.del(entity_id);
+    self.gravity_choice.del(entity_id);
+    self.history.del(entity_id);
+    self.engine.del(entity_id);
+    self.sensor.del(entity_id);
+    self.visible_object.del(entity_id);
+    self.owner.del(entity_id);
+    self.generated.del(entity_id);
+    self.anchor.del(entity_id);
+    self.nav.del(entity_id);
+    self.ai.del(entity_id);
+    self.lookahead.del(entity_id);
+    self.planet.del(entity_id);
+    self.body.del(entity_id);
+    self.orbit.del(entity_id);
+    self.landed.del(entity_id);
+    self.combat.del(entity_id);
+    self.target.del(entity_id);
+    self.disabled.del(entity_id);
+    self.ship_class.del(entity_id);
+    self.magazine.del(entity_id);
+    self.ordnance.del(entity_id);
+    self.launch.del(entity_id);
+    self.plot.del(entity_id);
+    self.entities.remove(entity_id);
+  }
+
+  // The displacement of the entity's next move: its velocity plus the gravity
+  // it picked up during the last one.
+  pub fn course(&self, entity_id: EntityId) -> Option<Hex> {
+    let velocity = *self.velocity.get(entity_id)?;
+    match self.pending_gravity.get(entity_id) {
+      Some(gravity) => Some(velocity + *gravity),
+      None => Some(velocity),
+    }
+  }
+
+  // The course once the burn plotted for this turn is added.
+  pub fn plotted_course(&self, entity_id: EntityId) -> Option<Hex> {
+    let course = self.course(entity_id)?;
+    match self.plot.get(entity_id) {
+      Some(thrust) => Some(course + *thrust),
+      None => Some(course),
+    }
+  }
+
+  // The most thrust the entity can put into its next move, including an
+  // overload burn if it has the fuel for one. Disabled ships and ships with
+  // no fuel left cannot thrust, apart from the free boost off a planet.
+  pub fn thrust_limit(&self, entity_id: EntityId) -> Option<i32> {
+    let engine = self.engine.get(entity_id)?;
+    if self.is_disabled(entity_id) || engine.fuel < 0 {
+      return Some(0);
+    }
+    let mut limit = self.free_thrust(entity_id) + engine.power + OVERLOAD_THRUST;
+    while limit > 0 && self.fuel_cost(entity_id, limit)? > engine.fuel {
+      limit -= 1;
+    }
+    Some(limit)
+  }
+
+  // Disabled entities remember the last turn they are out of action for.
+  pub fn is_disabled(&self, entity_id: EntityId) -> bool {
+    self.disabled_turns(entity_id) > 0
+  }
+
+  // Turns the entity stays disabled for, counting the current one.
+  pub fn disabled_turns(&self, entity_id: EntityId) -> i32 {
+    match self.disabled.get(entity_id) {
+      Some(last_turn) => (*last_turn - self.turn + 1).max(0),
+      None => 0,
+    }
+  }
+
+  // Entities of the same or allied factions are friends. Anything without an
+  // owner is a foe to everyone else.
+  pub fn relation(&self, a: EntityId, b: EntityId) -> Relation {
+    if a == b {
+      return Relation::Friend;
+    }
+    match (self.owner.get(a), self.owner.get(b)) {
+      (Some(x), Some(y)) => self.faction_relation(*x, *y),
+      _ => Relation::Foe,
+    }
+  }
+
+  pub fn faction_relation(&self, a: FactionId, b: FactionId) -> Relation {
+    let allied = |x: FactionId, y: FactionId| match self.factions.get(&x) {
+      Some(faction) => faction.allies.contains(&y),
+      None => false,
+    };
+    if a == b || allied(a, b) || allied(b, a) {
+      Relation::Friend
+    } else {
+      Relation::Foe
+    }
+  }
+
+  // Whether the entity's faction knows what the other entity is: its own
+  // entities always, anything else once its sensors have identified it.
+  // Entities without a faction only see for themselves.
+  pub fn identifies(&self, entity_id: EntityId, other: EntityId) -> bool {
+    if let Some(faction_id) = self.owner.get(entity_id) {
+      if let Some(faction) = self.factions.get(faction_id) {
+        if self.owner.get(other) == Some(faction_id) {
+          return true;
+        }
+        return faction.contacts.get(&other).map(|x| x.identified).unwrap_or(false);
+      }
+    }
+    match (self.position.get(entity_id), self.position.get(other)) {
+      (Some(a), Some(b)) => line_of_sight(self, *a, *b),
+      _ => false,
+    }
+  }
+
+  // Entities without a sensor of their own see as far as the world allows.
+  pub fn sensor_of(&self, entity_id: EntityId) -> Sensor {
+    match self.sensor.get(entity_id) {
+      Some(x) => *x,
+      None => Sensor {
+        range: self.vision_radius,
+        active: true,
+      },
+    }
+  }
+
+  pub fn player_faction(&self) -> Option<FactionId> {
+    self.owner.get(self.player?).copied()
+  }
+
+  pub fn display_name(&self, entity_id: EntityId) -> String {
+    match self.name.get(entity_id) {
+      Some(x) => x.clone(),
+      None => entity_id.to_string(),
+    }
+  }
+
+  // Fuel burnt by a move with the given thrust. Thrust up to the engine's
+  // power costs one fuel per unit and overloading costs extra.
+  pub fn fuel_cost(&self, entity_id: EntityId, thrust: i32) -> Option<i32> {
+    let engine = self.engine.get(entity_id)?;
+    let burn = (thrust - self.free_thrust(entity_id)).max(0);
+    let overload = (burn - engine.power).max(0);
+    Some(burn - overload + overload * OVERLOAD_FUEL_COST)
+  }
+
+  // Thrust that costs no fuel, currently only the boost off a planet surface.
+  pub fn free_thrust(&self, entity_id: EntityId) -> i32 {
+    if self.landed.has(entity_id) {
+      TAKEOFF_BOOST
+    } else {
+      0
+    }
+  }
+
+  // Whether a base sits on or next to the given hex.
+  pub fn base_near(&self, position: Hex) -> bool {
+    position.spiral(1).into_iter().chain(std::iter::once(position)).any(|x| {
+      let entity_ids = match self.position.at(x) {
+        Some(x) => x,
+        None => return false,
+      };
+      entity_ids.iter().any(|entity_id| {
+        matches!(self.visible_object.get(*entity_id), Some(VisibleObject(ObjectType::Base, _)))
+      })
+    })
+  }
+
+  // The planet whose surface covers the given hex, if any.
+  pub fn surface_body_at(&self, position: Hex) -> Option<EntityId> {
+    self.position.at(position)?.iter().find_map(|entity_id| {
+      match self.visible_object.get(*entity_id) {
+        Some(VisibleObject(ObjectType::Planet, _)) => self.body.get(*entity_id).copied(),
+        _ => None,
+      }
+    })
+  }
+
+  // The pull of any gravity hex at the given position.
+  pub fn gravity_pull_at(&self, position: Hex) -> Option<(i32, GravityStrength)> {
+    self.position.at(position)?.iter().find_map(|entity_id| {
+      let VisibleObject(object_type, _) = self.visible_object.get(*entity_id)?;
+      object_type.gravity_pull()
+    })
+  }
+
+  // The planet whose gravity ring contains the given hex, if any.
+  pub fn gravity_body_at(&self, position: Hex) -> Option<EntityId> {
+    self.position.at(position)?.iter().find_map(|entity_id| {
+      let VisibleObject(object_type, _) = self.visible_object.get(*entity_id)?;
+      object_type.gravity_pull()?;
+      self.body.get(*entity_id).copied()
+    })
+  }
+
+  pub fn rng(&mut self, name: &str) -> &mut Rng {
+    self.rng_streams.get(self.seed, name)
+  }
+
+  // A copy of the simulation to play futures out on. Events, trails and what
+  // the factions have seen before are left behind since no system reads
+  // them, and so is the generated terrain further than `radius` from
+  // `center`, which is most of the world and out of reach of the future.
+  pub fn snapshot(&self, center: Hex, radius: i32) -> Box<World> {
+    let keep = |entity_id| {
+      !self.generated.has(entity_id) || match self.position.get(entity_id) {
+        Some(position) => (*position - center).mag() <= radius,
+        None => true,
+      }
+    };
+    Box::new(Self {
+      seed: self.seed,
+      deltas: self.deltas.clone(),
+      loaded_chunks: self.loaded_chunks.clone(),
+      rng_streams: self.rng_streams.clone(),
+      player: self.player,
+      vision_radius: self.vision_radius,
+      factions: self.factions.iter().map(|(k, v)| (*k, v.without_memory())).collect(),
+      events: vec![],
+      need_simulate: self.need_simulate,
+      turn: self.turn,
+      phase: self.phase,
+      entities: self.entities.clone(),
+      name: self.name.filtered(keep),
+      position: self.position.filtered(keep),
+      velocity: self.velocity.filtered(keep),
+      pending_gravity: self.pending_gravity.filtered(keep),
+      gravity_choice: self.gravity_choice.filtered(keep),
+      history: Components::default(),
+      engine: self.engine.filtered(keep),
+      sensor: self.sensor.filtered(keep),
+      visible_object: self.visible_object.filtered(keep),
+      owner: self.owner.filtered(keep),
+      generated: self.generated.filtered(keep),
+      anchor: self.anchor.filtered(keep),
+      nav: self.nav.filtered(keep),
+      ai: self.ai.filtered(keep),
+      lookahead: self.lookahead.filtered(keep),
+      planet: self.planet.filtered(keep),
+      body: self.body.filtered(keep),
+      orbit: self.orbit.filtered(keep),
+      landed: self.landed.filtered(keep),
+      combat: self.combat.filtered(keep),
+      target: self.target.filtered(keep),
+      disabled: self.disabled.filtered(keep),
+      ship_class: self.ship_class.filtered(keep),
+      magazine: self.magazine.filtered(keep),
+      ordnance: self.ordnance.filtered(keep),
+      launch: self.launch.filtered(keep),
+      plot: self.plot.filtered(keep),
+    })
+  }
+
+  pub fn save(&self) -> SaveFile {
+    let mut save = SaveFile::new(SAVE_MIGRATIONS.len() as u32 + 1);
+    save.put("seed", &self.seed);
+    save.put("deltas", &self.deltas);
+    save.put("loaded_chunks", &self.loaded_chunks);
+    save.put("rng", &self.rng_streams);
+    save.put("turn", &self.turn);
+    save.put("phase", &self.phase);
+    save.put("player", &self.player);
+    save.put("vision_radius", &self.vision_radius);
+    save.put("factions", &self.factions);
+    save.put("entities", &self.entities);
+    save.put("name", &self.name);
+    save.put("position", &self.position);
+    save.put("velocity", &self.velocity);
+    save.put("pending_gravity", &self.pending_gravity);
+    save.put("gravity_choice", &self.gravity_choice);
+    save.put("history", &self.history);
+    save.put("engine", &self.engine);
+    save.put("sensor", &self.sensor);
+    save.put("visible_object", &self.visible_object);
+    save.put("owner", &self.owner);
+    save.put("generated", &self.generated);
+    save.put("anchor", &self.anchor);
+    save.put("nav", &self.nav);
+    save.put("ai", &self.ai);
+    save.put("lookahead", &self.lookahead);
+    save.put("planet", &self.planet);
+    save.put("body", &self.body);
+    save.put("orbit", &self.orbit);
+    save.put("landed", &self.landed);
+    save.put("combat", &self.combat);
+    save.put("target", &self.target);
+    save.put("disabled", &self.disabled);
+    save.put("ship_class", &self.ship_class);
+    save.put("magazine", &self.magazine);
+    save.put("ordnance", &self.ordnance);
+    save.put("launch", &self.launch);
+    save.put("plot", &self.plot);
+    save
+  }
+
+  // Two worlds with the same checksum hold the same simulation state; this is
+  // what determinism checks and replays compare.
+  pub fn checksum(&self) -> u32 {
+    hash_bytes_u32(0, self.save().to_text().as_bytes())
+  }
+
+  pub fn load(save: &SaveFile) -> Result<Box<Self>, SaveError> {
+    let mut world = World::new();
+    world.seed = save.get("seed")?;
+    world.deltas = save.get_or_default("deltas")?;
+    world.loaded_chunks = save.get_or_default("loaded_chunks")?;
+    world.rng_streams = save.get_or_default("rng")?;
+    world.turn = save.get("turn")?;
+    world.phase = save.get_or_default("phase")?;
+    world.player = save.get("player")?;
+    world.vision_radius = save.get("vision_radius")?;
+    world.factions = save.get_or_default("factions")?;
+    world.entities = save.get("entities")?;
+    world.name = save.get_or_default("name")?;
+    world.position = save.get_or_default("position")?;
+    world.velocity = save.get_or_default("velocity")?;
+    world.pending_gravity = save.get_or_default("pending_gravity")?;
+    world.gravity_choice = save.get_or_default("gravity_choice")?;
+    world.history = save.get_or_default("history")?;
+    world.engine = save.get_or_default("engine")?;
+    world.sensor = save.get_or_default("sensor")?;
+    world.visible_object = save.get_or_default("visible_object")?;
+    world.owner = save.get_or_default("owner")?;
+    world.generated = save.get_or_default("generated")?;
+    world.anchor = save.get_or_default("anchor")?;
+    world.nav = save.get_or_default("nav")?;
+    world.ai = save.get_or_default("ai")?;
+    world.lookahead = save.get_or_default("lookahead")?;
+    world.planet = save.get_or_default("planet")?;
+    world.body = save.get_or_default("body")?;
+    world.orbit = save.get_or_default("orbit")?;
+    world.landed = save.get_or_default("landed")?;
+    world.combat = save.get_or_default("combat")?;
+    world.target = save.get_or_default("target")?;
+    world.disabled = save.get_or_default("disabled")?;
+    world.ship_class = save.get_or_default("ship_class")?;
+    world.magazine = save.get_or_default("magazine")?;
+    world.ordnance = save.get_or_default("ordnance")?;
+    world.launch = save.get_or_default("launch")?;
+    world.plot = save.get_or_default("plot")?;
+    Ok(world)
+  }
+}
+
+pub fn generate_object(seed: u32, position: Hex) -> Option<VisibleObject> {
+  let seed = hash2_u32(seed, 4331);
+  if (position - hex(0, 0)).mag() < 4 {
+    return None;
+  }
+  let scale = 100.;
+  // sample the noise field in the same space the flat hex layout is drawn in
+  // so fields keep their shape on screen.
+  let x = (24 * position.q) as f32 / scale;
+  let y = (14 * (position.r + position.r + position.q)) as f32 / scale;
+  let v = fbm(2, 5., 0.3, seed, x, y);
+  let low = 0.55;
+  let high = 1.;
+  if v < low || v > high {
+    return None;
+  }
+  let c = remap(v, low, high, 0.3, 0.8);
+  let c = Color{ r: c, g: c, b: c, a: 1. };
+  Some(VisibleObject(ObjectType::Asteroid, c))
+}
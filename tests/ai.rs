@@ -0,0 +1,233 @@
+use triplanetary::*;
+
+mod common;
+use common::*;
+
+// Plays two AI ships against each other until one is destroyed, returning
+// the survivor and the final world.
+fn play_match(a: Difficulty, b: Difficulty, max_turns: i32) -> (Option<EntityId>, Box<World>) {
+  let mut world = World::new();
+  spawn_base(&mut world, "station", hex(0, 6), YELLOW);
+  let a = ai_ship(&mut world, "a", hex(-6, 0), a);
+  let b = ai_ship(&mut world, "b", hex(6, 0), b);
+  let mut counters = Counters::default();
+  for _ in 0..max_turns {
+    simulate_turn(&mut world, &mut counters);
+    match (world.position.has(a), world.position.has(b)) {
+      (true, false) => return (Some(a), world),
+      (false, true) => return (Some(b), world),
+      _ => {},
+    }
+  }
+  (None, world)
+}
+
+#[test]
+fn ai_vs_ai_match_is_decided() {
+  let (survivor, world) = play_match(Difficulty::Normal, Difficulty::Hard, 40);
+  assert!(survivor.is_some());
+  assert!(world.events.iter().any(|x| matches!(x, GameEvent::Destroyed { .. })));
+}
+
+#[test]
+fn ai_vs_ai_match_is_deterministic() {
+  let (survivor_a, world_a) = play_match(Difficulty::Hard, Difficulty::Hard, 40);
+  let (survivor_b, world_b) = play_match(Difficulty::Hard, Difficulty::Hard, 40);
+  assert_eq!(survivor_a, survivor_b);
+  assert_eq!(world_a.turn, world_b.turn);
+  assert_eq!(world_a.checksum(), world_b.checksum());
+}
+
+#[test]
+fn ai_retreats_to_refuel_when_low() {
+  let mut world = World::new();
+  let base = spawn_base(&mut world, "station", hex(8, 0), YELLOW);
+  let entity_id = ai_ship(&mut world, "a", hex(0, 0), Difficulty::Normal);
+  world.engine.get_mut(entity_id).unwrap().fuel = 3;
+  assert_eq!(best_intent(&world, entity_id), Intent::Refuel(base));
+  simulate_turn(&mut world, &mut Counters::default());
+  assert!(matches!(world.nav.get(entity_id), Some(Nav::GoTo(x)) if *x == hex(8, 0)));
+}
+
+#[test]
+fn ai_evades_incoming_torpedo() {
+  let mut world = World::new();
+  let enemy = ai_ship(&mut world, "enemy", hex(-10, 0), Difficulty::Normal);
+  let entity_id = ai_ship(&mut world, "a", hex(0, 0), Difficulty::Normal);
+  let torpedo = world.entities.create();
+  world.position.set(torpedo, hex(-3, 0));
+  world.velocity.set(torpedo, hex(3, 0));
+  world.visible_object.set(torpedo, VisibleObject(ObjectType::Torpedo, WHITE));
+  world.ordnance.set(torpedo, Ordnance {
+    kind: OrdnanceKind::Torpedo,
+    launcher: enemy,
+    launch_turn: 0,
+  });
+  assert_eq!(best_intent(&world, entity_id), Intent::Evade(torpedo));
+  assert_ne!(best_intent(&world, enemy), Intent::Evade(torpedo));
+}
+
+#[test]
+fn ai_goes_for_the_objective_when_unopposed() {
+  let mut world = World::new();
+  let entity_id = ai_ship(&mut world, "a", hex(0, 0), Difficulty::Normal);
+  world.ai.get_mut(entity_id).unwrap().objective = Some(hex(5, -2));
+  assert_eq!(best_intent(&world, entity_id), Intent::Objective(hex(5, -2)));
+  let mut counters = Counters::default();
+  for _ in 0..10 {
+    simulate_turn(&mut world, &mut counters);
+  }
+  assert_eq!(*world.position.get(entity_id).unwrap(), hex(5, -2));
+}
+
+#[test]
+fn difficulty_sets_the_odds_worth_firing_at() {
+  for (difficulty, fires) in [(Difficulty::Easy, false), (Difficulty::Hard, true)] {
+    let mut world = World::new();
+    let entity_id = ai_ship(&mut world, "a", hex(0, 0), difficulty);
+    let target = ai_ship(&mut world, "b", hex(1, 0), Difficulty::Easy);
+    world.ai.get_mut(target).unwrap().weights.attack = 0.;
+    simulate_turn(&mut world, &mut Counters::default());
+    let fired = world.events.iter().any(|x| matches!(x, GameEvent::Fired { entity_id: x, target: y, .. } if *x == entity_id && *y == target));
+    assert_eq!(fired, fires, "{difficulty:?}");
+  }
+}
+
+#[test]
+fn snapshots_step_like_the_world_without_touching_it() {
+  let (_, mut world) = play_match(Difficulty::Normal, Difficulty::Normal, 2);
+  let checksum = world.checksum();
+  let mut snapshot = world.snapshot(hex(0, 0), 100);
+  simulate_turn(&mut snapshot, &mut Counters::default());
+  assert_eq!(world.checksum(), checksum);
+  // what was seen before the snapshot is not part of it
+  for faction in world.factions.values_mut() {
+    *faction = faction.without_memory();
+  }
+  simulate_turn(&mut world, &mut Counters::default());
+  world.history = Components::default();
+  assert_eq!(snapshot.checksum(), world.checksum());
+}
+
+#[test]
+fn forked_rng_streams_roll_differently() {
+  let mut world = World::new();
+  world.rng("combat").roll(6);
+  let before = world.rng_streams.clone();
+  let rolls = |streams: &RngStreams| {
+    let mut streams = streams.clone();
+    let a: Vec<i32> = (0..8).map(|_| streams.get(12, "combat").roll(6)).collect();
+    let b: Vec<i32> = (0..8).map(|_| streams.get(12, "hazard").roll(6)).collect();
+    (a, b)
+  };
+  let (a1, b1) = rolls(&world.rng_streams.fork(1));
+  let (a2, b2) = rolls(&world.rng_streams.fork(2));
+  assert_ne!(a1, a2);
+  assert_ne!(b1, b2);
+  assert_eq!(rolls(&before), rolls(&world.rng_streams));
+}
+
+#[test]
+fn lookahead_burns_toward_the_objective() {
+  let mut world = World::new();
+  let entity_id = ai_ship(&mut world, "a", hex(0, 0), Difficulty::Normal);
+  world.ai.get_mut(entity_id).unwrap().objective = Some(hex(8, 0));
+  world.lookahead.set(entity_id, Lookahead { depth: 3, rollouts: 7 });
+  assert_eq!(choose_burn(&world, entity_id, *world.lookahead.get(entity_id).unwrap()), Some(hex(1, 0)));
+  let mut counters = Counters::default();
+  for _ in 0..12 {
+    simulate_turn(&mut world, &mut counters);
+  }
+  assert!((*world.position.get(entity_id).unwrap() - hex(8, 0)).mag() <= 1);
+}
+
+#[test]
+fn lookahead_budget_limits_the_burns_tried() {
+  let mut world = World::new();
+  let entity_id = ai_ship(&mut world, "a", hex(0, 0), Difficulty::Normal);
+  ai_ship(&mut world, "b", hex(5, 0), Difficulty::Normal);
+  let budget = Lookahead { depth: 2, rollouts: 1 };
+  assert_eq!(choose_burn(&world, entity_id, budget), Some(hex(0, 0)));
+  let budget = Lookahead { depth: 2, rollouts: 14 };
+  assert_eq!(choose_burn(&world, entity_id, budget), choose_burn(&world, entity_id, budget));
+}
+
+#[test]
+fn lookahead_ai_plays_a_match() {
+  let mut world = World::new();
+  let a = ai_ship(&mut world, "a", hex(-6, 0), Difficulty::Hard);
+  let b = ai_ship(&mut world, "b", hex(6, 0), Difficulty::Hard);
+  world.lookahead.set(a, Lookahead { depth: 3, rollouts: 14 });
+  let mut counters = Counters::default();
+  for _ in 0..40 {
+    simulate_turn(&mut world, &mut counters);
+    if !world.position.has(a) || !world.position.has(b) {
+      break;
+    }
+  }
+  assert!(!world.position.has(a) || !world.position.has(b));
+}
+
+#[test]
+fn lookahead_only_plays_against_identified_enemies() {
+  let (mut world, blue, red) = two_factions();
+  let entity_id = ai_ship(&mut world, "a", hex(0, 0), Difficulty::Normal);
+  world.owner.set(entity_id, blue);
+  world.sensor.set(entity_id, Sensor { range: 2, active: false });
+  let mut unseen = world.snapshot(hex(0, 0), 100);
+  let enemy = ai_ship(&mut world, "b", hex(4, 0), Difficulty::Hard);
+  world.owner.set(enemy, red);
+  world.sensor.set(enemy, Sensor { range: 10, active: true });
+  simulate_vision_system(&mut world);
+  simulate_vision_system(&mut unseen);
+  assert!(!world.identifies(entity_id, enemy));
+  for thrust in [hex(0, 0), hex(1, 0), hex(-1, 0)] {
+    assert_eq!(rollout_value(&world, entity_id, thrust, 3, 0), rollout_value(&unseen, entity_id, thrust, 3, 0));
+  }
+}
+
+#[test]
+fn lookahead_cannot_see_plotted_burns() {
+  let (mut world, blue, red) = two_factions();
+  world.vision_radius = 8;
+  let entity_id = ai_ship(&mut world, "a", hex(0, 0), Difficulty::Normal);
+  world.owner.set(entity_id, blue);
+  // unarmed, so no AI takes over its burn in the rollouts, and fast enough
+  // to ram the AI ship if its plot were known
+  let player = ship_with_engine(&mut world, hex(6, 0), hex(0, 0), 6, 20);
+  world.owner.set(player, red);
+  simulate_vision_system(&mut world);
+  let budget = Lookahead { depth: 3, rollouts: 7 };
+  let values = |world: &World| -> Vec<f32> {
+    [hex(0, 0), hex(1, 0), hex(-1, 0)].iter()
+      .map(|x| rollout_value(world, entity_id, *x, 3, 0))
+      .collect()
+  };
+  let before = (values(&world), choose_burn(&world, entity_id, budget));
+  for thrust in [hex(-6, 0), hex(-5, 0), hex(-6, 1)] {
+    world.plot.set(player, thrust);
+    assert_eq!((values(&world), choose_burn(&world, entity_id, budget)), before);
+  }
+}
+
+#[test]
+fn torpedoes_are_aimed_without_seeing_the_target_burn() {
+  let (mut world, blue, red) = two_factions();
+  let entity_id = ai_ship(&mut world, "a", hex(0, 0), Difficulty::Normal);
+  world.owner.set(entity_id, blue);
+  let target = owned_ship(&mut world, hex(4, 0), red);
+  world.velocity.set(target, hex(0, 1));
+  simulate_vision_system(&mut world);
+  world.phase = Phase::Ordnance;
+  let launches = |world: &World| {
+    let mut world = world.snapshot(hex(0, 0), 20);
+    simulate_ai_ordnance_system(&mut world);
+    world.launch.get(entity_id).cloned()
+  };
+  let before = launches(&world);
+  assert!(before.is_some());
+  for thrust in [hex(1, 0), hex(0, -1), hex(-1, 1)] {
+    world.plot.set(target, thrust);
+    assert_eq!(launches(&world), before);
+  }
+}
@@ -0,0 +1,62 @@
+// Fixtures shared by the integration tests. Every test binary compiles this
+// module but only uses some of it.
+#![allow(dead_code)]
+
+use triplanetary::*;
+
+// An unarmed ship with the given engine power and a full tank of `fuel`.
+pub fn ship_with_engine(world: &mut World, position: Hex, velocity: Hex, power: i32, fuel: i32) -> EntityId {
+  let entity_id = world.entities.create();
+  world.position.set(entity_id, position);
+  world.velocity.set(entity_id, velocity);
+  world.visible_object.set(entity_id, VisibleObject(ObjectType::Ship, WHITE));
+  world.engine.set(entity_id, Engine {
+    power,
+    thrust_applied: 0,
+    fuel,
+    fuel_capacity: fuel,
+  });
+  entity_id
+}
+
+// An armed ship at rest with a standard engine and a full tank.
+pub fn ship(world: &mut World, position: Hex) -> EntityId {
+  let entity_id = ship_with_engine(world, position, hex(0, 0), 1, DEFAULT_FUEL_CAPACITY);
+  world.combat.set(entity_id, CombatStrength { attack: 2, defence: 2 });
+  entity_id
+}
+
+pub fn owned_ship(world: &mut World, position: Hex, faction_id: FactionId) -> EntityId {
+  let entity_id = ship(world, position);
+  world.owner.set(entity_id, faction_id);
+  entity_id
+}
+
+// A corvette with a full magazine flown by the AI.
+pub fn ai_ship(world: &mut World, name: &str, position: Hex, difficulty: Difficulty) -> EntityId {
+  let entity_id = ship(world, position);
+  world.name.set(entity_id, name.to_string());
+  world.ship_class.set(entity_id, "corvette".to_string());
+  world.magazine.set(entity_id, Magazine::full(ship_class("corvette").unwrap()));
+  world.ai.set(entity_id, Ai::new(difficulty));
+  entity_id
+}
+
+pub fn asteroid(world: &mut World, position: Hex) -> EntityId {
+  let entity_id = world.entities.create();
+  world.position.set(entity_id, position);
+  world.visible_object.set(entity_id, VisibleObject(ObjectType::Asteroid, GRAY));
+  entity_id
+}
+
+// Two hostile factions, blue and red.
+pub fn two_factions() -> (Box<World>, FactionId, FactionId) {
+  let mut world = World::new();
+  world.factions.insert(FactionId(0), Faction::new("blue", BLUE));
+  world.factions.insert(FactionId(1), Faction::new("red", RED));
+  (world, FactionId(0), FactionId(1))
+}
+
+pub fn distance(world: &World, a: EntityId, b: EntityId) -> i32 {
+  (*world.position.get(a).unwrap() - *world.position.get(b).unwrap()).mag()
+}
@@ -0,0 +1,140 @@
+use triplanetary::*;
+
+// A world with a player ship at the origin and the asteroids around it
+// generated.
+fn explorer() -> Box<World> {
+  let mut world = World::new();
+  world.seed = 12;
+  world.vision_radius = 20;
+  let entity_id = world.entities.create();
+  world.position.set(entity_id, hex(0, 0));
+  world.history.set(entity_id, vec![HistoryEvent { position: hex(0, 0), thrust_applied: 0 }].into_iter().collect());
+  world.player = Some(entity_id);
+  simulate_generate_around_anchors_system(&mut world);
+  world
+}
+
+fn fly_to(world: &mut World, position: Hex) {
+  let entity_id = world.player.unwrap();
+  world.position.set(entity_id, position);
+  world.history.get_mut(entity_id).unwrap().push_back(HistoryEvent { position, thrust_applied: 0 });
+  simulate_generate_around_anchors_system(world);
+}
+
+fn asteroids(world: &World) -> Vec<(Hex, VisibleObject)> {
+  let mut results: Vec<_> = world.entities.all()
+    .filter(|x| world.generated.has(*x))
+    .map(|x| (*world.position.get(x).unwrap(), *world.visible_object.get(x).unwrap()))
+    .collect();
+  results.sort_by_key(|(x, _)| (x.q, x.r));
+  results
+}
+
+fn first_asteroid(world: &World) -> EntityId {
+  world.entities.all().find(|x| world.generated.has(*x)).expect("no asteroids generated")
+}
+
+#[test]
+fn regenerated_hexes_match_when_nothing_changed() {
+  let mut world = explorer();
+  let before = asteroids(&world);
+  fly_to(&mut world, hex(100, 0));
+  fly_to(&mut world, hex(0, 0));
+  assert_eq!(asteroids(&world), before);
+  assert!(world.deltas.chunks.is_empty());
+}
+
+#[test]
+fn destroyed_asteroids_stay_destroyed() {
+  let mut world = explorer();
+  let entity_id = first_asteroid(&world);
+  let position = *world.position.get(entity_id).unwrap();
+  world.remove(entity_id);
+  let before = asteroids(&world);
+  fly_to(&mut world, hex(100, 0));
+  assert_eq!(world.deltas.chunks[&chunk_of(position)].hexes[&(position.q, position.r)], None);
+  let mut loaded = World::load(&world.save()).unwrap();
+  assert_eq!(loaded.deltas, world.deltas);
+  fly_to(&mut world, hex(0, 0));
+  assert_eq!(asteroids(&world), before);
+  fly_to(&mut loaded, hex(0, 0));
+  assert_eq!(asteroids(&loaded), before);
+}
+
+#[test]
+fn moved_asteroids_come_back_where_they_were_left() {
+  let mut world = explorer();
+  let entity_id = first_asteroid(&world);
+  let object = *world.visible_object.get(entity_id).unwrap();
+  let destination = hex(0, 1);
+  assert!(world.position.at(destination).map(|x| x.is_empty()).unwrap_or(true));
+  world.position.set(entity_id, destination);
+  let before = asteroids(&world);
+  fly_to(&mut world, hex(100, 0));
+  fly_to(&mut world, hex(0, 0));
+  assert_eq!(asteroids(&world), before);
+  assert_eq!(world.generated_object(destination), Some(object));
+}
+
+#[test]
+fn streaming_only_unloads_generated_entities() {
+  let mut world = explorer();
+  let drifter = world.entities.create();
+  world.position.set(drifter, hex(5, 0));
+  world.velocity.set(drifter, hex(1, 0));
+  world.visible_object.set(drifter, VisibleObject(ObjectType::Ship, RED));
+  fly_to(&mut world, hex(100, 0));
+  // the asteroids around it are gone but the ship is still there, moving
+  assert!(asteroids(&world).iter().all(|(x, _)| x.mag() > 20));
+  simulate_movement_system(&mut world);
+  assert_eq!(world.position.get(drifter), Some(&hex(6, 0)));
+}
+
+#[test]
+fn every_anchor_keeps_its_area_loaded() {
+  let mut world = explorer();
+  let objective = world.entities.create();
+  world.position.set(objective, hex(0, 0));
+  world.anchor.set(objective, 10);
+  let before: Vec<_> = asteroids(&world).into_iter()
+    .filter(|(x, _)| x.mag() <= 10)
+    .collect();
+  assert!(!before.is_empty());
+  fly_to(&mut world, hex(100, 0));
+  let after = asteroids(&world);
+  assert!(before.iter().all(|x| after.contains(x)));
+  assert!(world.loaded_chunks.contains(&chunk_of(hex(100, 0))));
+}
+
+#[test]
+fn all_player_ships_are_anchors() {
+  let mut world = explorer();
+  let player_entity_id = world.player.unwrap();
+  world.factions.insert(FactionId(0), Faction::new("player", GREEN));
+  world.owner.set(player_entity_id, FactionId(0));
+  world.visible_object.set(player_entity_id, VisibleObject(ObjectType::Ship, GREEN));
+  let wingman = world.entities.create();
+  world.position.set(wingman, hex(0, 100));
+  world.visible_object.set(wingman, VisibleObject(ObjectType::Ship, GREEN));
+  world.owner.set(wingman, FactionId(0));
+  assert_eq!(load_anchors(&world), vec![(hex(0, 0), 20), (hex(0, 100), 20)]);
+  simulate_generate_around_anchors_system(&mut world);
+  assert!(world.loaded_chunks.contains(&chunk_of(hex(0, 0))));
+  assert!(world.loaded_chunks.contains(&chunk_of(hex(0, 100))));
+}
+
+#[test]
+fn snapshots_leave_far_terrain_and_memory_behind() {
+  let mut world = explorer();
+  let mut faction = Faction::new("player", GREEN);
+  faction.explored.insert(hex(0, 0), Sighting { turn: 1, objects: vec![] });
+  world.factions.insert(FactionId(0), faction);
+  let snapshot = world.snapshot(hex(0, 0), 10);
+  let near: Vec<_> = asteroids(&world).into_iter()
+    .filter(|(x, _)| x.mag() <= 10)
+    .collect();
+  assert_eq!(asteroids(&snapshot), near);
+  assert_eq!(snapshot.position.get(world.player.unwrap()), Some(&hex(0, 0)));
+  assert!(snapshot.factions[&FactionId(0)].explored.is_empty());
+  assert_eq!(world.factions[&FactionId(0)].explored.len(), 1);
+}
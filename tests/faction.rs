@@ -0,0 +1,165 @@
+use std::collections::BTreeMap;
+use triplanetary::*;
+
+mod common;
+use common::*;
+
+// Two hostile factions with a short sight range.
+fn short_sighted_factions() -> (Box<World>, FactionId, FactionId) {
+  let (mut world, blue, red) = two_factions();
+  world.vision_radius = 4;
+  (world, blue, red)
+}
+
+#[test]
+fn factions_see_from_all_of_their_ships() {
+  let (mut world, blue, red) = short_sighted_factions();
+  owned_ship(&mut world, hex(0, 0), blue);
+  owned_ship(&mut world, hex(20, 0), blue);
+  owned_ship(&mut world, hex(0, 20), red);
+  simulate_vision_system(&mut world);
+  let blue_faction = &world.factions[&blue];
+  assert!(blue_faction.visible.contains(&hex(3, 0)));
+  assert!(blue_faction.visible.contains(&hex(17, 0)));
+  assert!(!blue_faction.visible.contains(&hex(0, 17)));
+  assert!(world.factions[&red].visible.contains(&hex(0, 17)));
+  assert!(!world.factions[&red].visible.contains(&hex(3, 0)));
+}
+
+#[test]
+fn asteroids_block_faction_sight() {
+  let (mut world, blue, _) = short_sighted_factions();
+  owned_ship(&mut world, hex(0, 0), blue);
+  asteroid(&mut world, hex(1, 0));
+  simulate_vision_system(&mut world);
+  assert!(world.factions[&blue].visible.contains(&hex(1, 0)));
+  assert!(!world.factions[&blue].visible.contains(&hex(3, 0)));
+}
+
+#[test]
+fn explored_hexes_are_remembered_and_saved() {
+  let (mut world, blue, _) = short_sighted_factions();
+  let entity_id = owned_ship(&mut world, hex(0, 0), blue);
+  simulate_vision_system(&mut world);
+  world.position.set(entity_id, hex(30, 0));
+  simulate_vision_system(&mut world);
+  let faction = &world.factions[&blue];
+  assert!(!faction.visible.contains(&hex(-3, 0)));
+  assert!(faction.explored.contains_key(&hex(-3, 0)));
+  assert!(faction.explored.contains_key(&hex(33, 0)));
+  assert!(!faction.explored.contains_key(&hex(15, 0)));
+  let mut loaded = World::load(&world.save()).unwrap();
+  assert_eq!(loaded.factions[&blue].explored, faction.explored);
+  assert_eq!(loaded.checksum(), world.checksum());
+  simulate_vision_system(&mut loaded);
+  assert_eq!(loaded.factions[&blue].visible, faction.visible);
+}
+
+#[test]
+fn last_sightings_are_remembered_out_of_sight() {
+  let (mut world, blue, red) = short_sighted_factions();
+  let scout = owned_ship(&mut world, hex(0, 0), blue);
+  let enemy = owned_ship(&mut world, hex(3, 0), red);
+  asteroid(&mut world, hex(0, 2));
+  world.turn = 5;
+  simulate_vision_system(&mut world);
+  world.turn = 6;
+  world.position.set(scout, hex(30, 0));
+  world.position.set(enemy, hex(-10, 0));
+  simulate_vision_system(&mut world);
+  let explored = &world.factions[&blue].explored;
+  assert_eq!(explored[&hex(3, 0)], Sighting {
+    turn: 5,
+    objects: vec![VisibleObject(ObjectType::Ship, WHITE)],
+  });
+  assert_eq!(explored[&hex(0, 2)].objects, vec![VisibleObject(ObjectType::Asteroid, GRAY)]);
+  // the faction's own ships are not remembered where they used to be
+  assert!(explored[&hex(0, 0)].objects.is_empty());
+  assert_eq!(explored[&hex(30, 0)].turn, 6);
+}
+
+#[test]
+fn allies_are_friends_and_everyone_else_a_foe() {
+  let (mut world, blue, red) = short_sighted_factions();
+  let green = FactionId(2);
+  let mut green_faction = Faction::new("green", GREEN);
+  green_faction.allies.insert(blue);
+  world.factions.insert(green, green_faction);
+  let a = owned_ship(&mut world, hex(0, 0), blue);
+  let b = owned_ship(&mut world, hex(1, 0), blue);
+  let c = owned_ship(&mut world, hex(2, 0), red);
+  let d = owned_ship(&mut world, hex(3, 0), green);
+  let e = ship(&mut world, hex(4, 0));
+  let f = ship(&mut world, hex(5, 0));
+  assert_eq!(world.relation(a, b), Relation::Friend);
+  assert_eq!(world.relation(a, c), Relation::Foe);
+  assert_eq!(world.relation(a, d), Relation::Friend);
+  assert_eq!(world.relation(d, a), Relation::Friend);
+  assert_eq!(world.relation(c, d), Relation::Foe);
+  assert_eq!(world.relation(a, e), Relation::Foe);
+  assert_eq!(world.relation(e, f), Relation::Foe);
+  assert_eq!(world.relation(e, e), Relation::Friend);
+}
+
+#[test]
+fn ai_acts_on_what_its_own_faction_sees() {
+  let (mut world, blue, red) = short_sighted_factions();
+  let green = FactionId(2);
+  let mut green_faction = Faction::new("green", GREEN);
+  green_faction.allies.insert(blue);
+  world.factions.insert(green, green_faction);
+  let hunter = owned_ship(&mut world, hex(0, 0), blue);
+  world.ai.set(hunter, Ai::new(Difficulty::Normal));
+  let enemy = owned_ship(&mut world, hex(10, 0), red);
+  // only an allied scout can see the enemy at first
+  let scout = owned_ship(&mut world, hex(8, 0), green);
+  simulate_vision_system(&mut world);
+  assert!(world.factions[&green].visible.contains(&hex(10, 0)));
+  assert_eq!(best_intent(&world, hunter), Intent::Idle);
+  // a scout of its own faction shares its sight
+  world.owner.set(scout, blue);
+  simulate_vision_system(&mut world);
+  assert_eq!(best_intent(&world, hunter), Intent::Pursue(enemy));
+}
+
+#[test]
+fn ai_leaves_friendly_ships_alone() {
+  let (mut world, blue, _) = short_sighted_factions();
+  let a = owned_ship(&mut world, hex(0, 0), blue);
+  let b = owned_ship(&mut world, hex(1, 0), blue);
+  world.ai.set(a, Ai::new(Difficulty::Hard));
+  world.ai.set(b, Ai::new(Difficulty::Hard));
+  simulate_vision_system(&mut world);
+  assert_eq!(best_intent(&world, a), Intent::Idle);
+  assert_eq!(best_intent(&world, b), Intent::Idle);
+}
+
+#[test]
+fn ordnance_belongs_to_the_launcher_faction() {
+  let (mut world, blue, _) = short_sighted_factions();
+  let launcher = owned_ship(&mut world, hex(0, 0), blue);
+  let torpedo = spawn_ordnance(&mut world, launcher, OrdnanceKind::Torpedo, hex(1, 0)).unwrap();
+  assert_eq!(world.owner.get(torpedo), Some(&blue));
+  assert_eq!(world.relation(launcher, torpedo), Relation::Friend);
+}
+
+#[test]
+fn saves_from_before_sightings_still_load() {
+  let (mut world, blue, red) = short_sighted_factions();
+  let entity_id = owned_ship(&mut world, hex(0, 0), red);
+  let mut save = world.save();
+  type Old = (((String, Color), Vec<FactionId>), (Vec<(i32, i32)>, BTreeMap<EntityId, Contact>));
+  let contacts: BTreeMap<_, _> = vec![(entity_id, Contact { position: hex(0, 0), identified: true })].into_iter().collect();
+  let old: BTreeMap<FactionId, Old> = vec![
+    (blue, ((("blue".to_string(), BLUE), vec![red]), (vec![(2, -1)], contacts))),
+    (red, ((("red".to_string(), RED), vec![]), (vec![], BTreeMap::new()))),
+  ].into_iter().collect();
+  save.put("factions", &old);
+  save.version = 3;
+  let save = SaveFile::parse(&save.to_text(), SAVE_MIGRATIONS).unwrap();
+  let loaded = World::load(&save).unwrap();
+  let faction = &loaded.factions[&blue];
+  assert!(faction.allies.contains(&red));
+  assert_eq!(faction.explored[&hex(2, -1)], Sighting { turn: 0, objects: vec![] });
+  assert_eq!(faction.contacts[&entity_id].position, hex(0, 0));
+}
@@ -0,0 +1,274 @@
+use triplanetary::*;
+
+mod common;
+use common::*;
+
+// Runs turns until the seeker ends a turn on the target, returning how many
+// turns that took.
+fn turns_to_meet(world: &mut World, seeker: EntityId, target: EntityId, max_turns: i32) -> Option<i32> {
+  let mut counters = Counters::default();
+  for turn in 1..=max_turns {
+    simulate_turn(world, &mut counters);
+    if distance(world, seeker, target) == 0 {
+      return Some(turn);
+    }
+  }
+  None
+}
+
+#[test]
+fn seek_with_no_engine_power_reports_no_intercept() {
+  let mut world = World::new();
+  let seeker = ship_with_engine(&mut world, hex(0, 0), hex(0, 0), 0, 20);
+  let target = ship_with_engine(&mut world, hex(10, 0), hex(0, 0), 0, 20);
+  world.nav.set(seeker, Nav::Seek(target));
+  let result = solve_intercept(&world, seeker, target, 0, false);
+  assert_eq!(result, Some(Intercept::Unreachable));
+  simulate_turn(&mut world, &mut Counters::default());
+  assert!(world.events.contains(&GameEvent::NoIntercept { entity_id: seeker, target }));
+  assert_eq!(*world.position.get(seeker).unwrap(), hex(0, 0));
+}
+
+#[test]
+fn seek_with_no_fuel_reports_no_intercept() {
+  let mut world = World::new();
+  let seeker = ship_with_engine(&mut world, hex(0, 0), hex(0, 0), 1, 0);
+  let target = ship_with_engine(&mut world, hex(10, 0), hex(0, 0), 1, 20);
+  assert_eq!(solve_intercept(&world, seeker, target, 1, false), Some(Intercept::Unreachable));
+}
+
+#[test]
+fn thrust_limit_bottoms_out_without_fuel() {
+  let mut world = World::new();
+  let entity_id = ship_with_engine(&mut world, hex(0, 0), hex(0, 0), 2, 0);
+  assert_eq!(world.thrust_limit(entity_id), Some(0));
+  world.engine.get_mut(entity_id).unwrap().fuel = -3;
+  assert_eq!(world.thrust_limit(entity_id), Some(0));
+  world.engine.get_mut(entity_id).unwrap().fuel = 1;
+  assert_eq!(world.thrust_limit(entity_id), Some(1));
+}
+
+#[test]
+fn seek_drifting_into_the_target_needs_no_engine() {
+  let mut world = World::new();
+  let seeker = ship_with_engine(&mut world, hex(0, 0), hex(2, 0), 0, 0);
+  let target = ship_with_engine(&mut world, hex(6, 0), hex(0, 0), 0, 0);
+  assert_eq!(
+    solve_intercept(&world, seeker, target, 0, false),
+    Some(Intercept::Found { course: hex(2, 0), turns: 3 }),
+  );
+}
+
+#[test]
+fn seek_target_at_equal_speed() {
+  let mut world = World::new();
+  let seeker = ship_with_engine(&mut world, hex(0, 0), hex(2, 0), 1, 20);
+  let target = ship_with_engine(&mut world, hex(5, 0), hex(2, 0), 1, 20);
+  world.nav.set(seeker, Nav::Seek(target));
+  assert!(turns_to_meet(&mut world, seeker, target, 10).is_some());
+}
+
+#[test]
+fn seek_fleeing_target() {
+  let mut world = World::new();
+  let seeker = ship_with_engine(&mut world, hex(0, 0), hex(0, 0), 1, 20);
+  let target = ship_with_engine(&mut world, hex(4, 0), hex(1, 0), 1, 20);
+  world.nav.set(seeker, Nav::Seek(target));
+  assert!(turns_to_meet(&mut world, seeker, target, INTERCEPT_MAX_TURNS).is_some());
+}
+
+#[test]
+fn seek_target_fleeing_out_of_reach() {
+  let mut world = World::new();
+  let seeker = ship_with_engine(&mut world, hex(0, 0), hex(0, 0), 1, 3);
+  let target = ship_with_engine(&mut world, hex(4, 0), hex(3, 0), 1, 20);
+  world.nav.set(seeker, Nav::Seek(target));
+  assert_eq!(solve_intercept(&world, seeker, target, 1, false), Some(Intercept::Unreachable));
+  let mut counters = Counters::default();
+  for _ in 0..5 {
+    simulate_turn(&mut world, &mut counters);
+    assert!(world.events.contains(&GameEvent::NoIntercept { entity_id: seeker, target }));
+  }
+}
+
+#[test]
+fn rendezvous_matches_course_on_arrival() {
+  let mut world = World::new();
+  let seeker = ship_with_engine(&mut world, hex(0, 0), hex(0, 0), 1, 20);
+  let target = ship_with_engine(&mut world, hex(6, -3), hex(1, 0), 1, 20);
+  world.nav.set(seeker, Nav::Rendezvous(target));
+  assert!(turns_to_meet(&mut world, seeker, target, INTERCEPT_MAX_TURNS).is_some());
+  let relative_speed = (world.course(seeker).unwrap() - world.course(target).unwrap()).mag();
+  assert!(relative_speed <= DOCKING_SPEED);
+}
+
+#[test]
+fn predicted_drift_bends_through_gravity() {
+  let mut world = World::new();
+  spawn_planet(&mut world, "planet", hex(0, 0), 0, GravityStrength::Full, WHITE);
+  let path = predict_drift(&world, hex(-3, 1), hex(1, 0), 4);
+  let straight = [hex(-2, 1), hex(-1, 1), hex(0, 1), hex(1, 1)];
+  assert_eq!(path[0], straight[0]);
+  assert_ne!(path[3], straight[3]);
+}
+
+// Ends a turn and checks the ship only burned what its engine and fuel allow.
+fn end_turn_within_engine(world: &mut World, counters: &mut Counters, entity_id: EntityId) {
+  let course = world.course(entity_id).unwrap();
+  let engine = *world.engine.get(entity_id).unwrap();
+  simulate_turn(world, counters);
+  let new_engine = world.engine.get(entity_id).unwrap();
+  assert!((*world.velocity.get(entity_id).unwrap() - course).mag() <= engine.power);
+  assert!(new_engine.fuel >= 0);
+  assert!(engine.fuel - new_engine.fuel <= engine.power);
+}
+
+#[test]
+fn patrol_loops_through_waypoints() {
+  let mut world = World::new();
+  let waypoints = vec![hex(4, 0), hex(4, -4), hex(0, 0)];
+  let patroller = ship_with_engine(&mut world, hex(0, 0), hex(0, 0), 1, 40);
+  world.nav.set(patroller, Nav::Patrol(waypoints.clone()));
+  let mut counters = Counters::default();
+  let mut visited = vec![];
+  for _ in 0..40 {
+    end_turn_within_engine(&mut world, &mut counters, patroller);
+    let position = *world.position.get(patroller).unwrap();
+    if waypoints.contains(&position) && visited.last() != Some(&position) {
+      visited.push(position);
+    }
+  }
+  assert!(visited.len() > waypoints.len());
+  for (index, position) in visited.iter().enumerate() {
+    assert_eq!(*position, waypoints[index % waypoints.len()]);
+  }
+}
+
+#[test]
+fn follow_holds_station_off_the_leader() {
+  let mut world = World::new();
+  let leader = ship_with_engine(&mut world, hex(0, 0), hex(1, 0), 1, 20);
+  let follower = ship_with_engine(&mut world, hex(-5, 4), hex(0, 0), 1, 20);
+  world.nav.set(follower, Nav::Follow(leader, hex(-1, 2)));
+  let mut counters = Counters::default();
+  for _ in 0..15 {
+    end_turn_within_engine(&mut world, &mut counters, follower);
+  }
+  let leader_position = *world.position.get(leader).unwrap();
+  assert_eq!(*world.position.get(follower).unwrap(), leader_position + hex(-1, 2));
+  assert_eq!(world.course(follower), world.course(leader));
+}
+
+#[test]
+fn flee_opens_the_distance() {
+  let mut world = World::new();
+  let threat = ship_with_engine(&mut world, hex(0, 0), hex(0, 0), 1, 20);
+  let fleeing = ship_with_engine(&mut world, hex(2, -1), hex(0, 0), 1, 20);
+  world.nav.set(fleeing, Nav::Flee(threat));
+  let mut counters = Counters::default();
+  let mut last_distance = distance(&world, threat, fleeing);
+  for _ in 0..6 {
+    end_turn_within_engine(&mut world, &mut counters, fleeing);
+    let new_distance = distance(&world, threat, fleeing);
+    assert!(new_distance > last_distance);
+    last_distance = new_distance;
+  }
+}
+
+#[test]
+fn flee_without_fuel_only_drifts() {
+  let mut world = World::new();
+  let threat = ship_with_engine(&mut world, hex(0, 0), hex(0, 0), 1, 20);
+  let fleeing = ship_with_engine(&mut world, hex(2, 0), hex(0, 1), 1, 0);
+  world.nav.set(fleeing, Nav::Flee(threat));
+  simulate_turn(&mut world, &mut Counters::default());
+  assert_eq!(*world.position.get(fleeing).unwrap(), hex(2, 1));
+  assert_eq!(world.course(fleeing), Some(hex(0, 1)));
+}
+
+#[test]
+fn escort_closes_in_and_stays_close() {
+  let mut world = World::new();
+  let protected = ship_with_engine(&mut world, hex(0, 0), hex(1, 0), 1, 20);
+  let escort = ship_with_engine(&mut world, hex(-4, 6), hex(0, 0), 1, 20);
+  world.nav.set(escort, Nav::Escort(protected));
+  let mut counters = Counters::default();
+  let mut closed_in = false;
+  for _ in 0..20 {
+    end_turn_within_engine(&mut world, &mut counters, escort);
+    let close = distance(&world, protected, escort) <= ESCORT_RADIUS;
+    assert!(close || !closed_in);
+    closed_in |= close;
+  }
+  assert!(closed_in);
+}
+
+#[test]
+fn nav_orders_survive_a_save() {
+  let mut world = World::new();
+  let a = ship_with_engine(&mut world, hex(0, 0), hex(0, 0), 1, 20);
+  let b = ship_with_engine(&mut world, hex(3, 0), hex(0, 0), 1, 20);
+  world.nav.set(a, Nav::Patrol(vec![hex(1, 0), hex(2, -2)]));
+  world.nav.set(b, Nav::Follow(a, hex(0, 1)));
+  let loaded = World::load(&world.save()).unwrap();
+  assert_eq!(format!("{:?}", loaded.nav.get(a)), format!("{:?}", world.nav.get(a)));
+  assert_eq!(format!("{:?}", loaded.nav.get(b)), format!("{:?}", world.nav.get(b)));
+}
+
+// Runs turns until the ship has sat still on the target for a full turn,
+// returning how many turns that took.
+fn turns_to_park(world: &mut World, entity_id: EntityId, target_position: Hex, max_turns: i32) -> Option<i32> {
+  let mut counters = Counters::default();
+  for turn in 1..=max_turns {
+    let position = *world.position.get(entity_id).unwrap();
+    end_turn_within_engine(world, &mut counters, entity_id);
+    assert!(!world.events.iter().any(|x| matches!(x, GameEvent::AsteroidHazard { .. } | GameEvent::Crashed { .. })));
+    if position == target_position && *world.position.get(entity_id).unwrap() == target_position {
+      return Some(turn);
+    }
+  }
+  None
+}
+
+#[test]
+fn go_to_stops_on_the_target() {
+  let mut world = World::new();
+  let entity_id = ship_with_engine(&mut world, hex(0, 0), hex(0, 0), 1, 20);
+  world.nav.set(entity_id, Nav::GoTo(hex(9, -4)));
+  assert!(turns_to_park(&mut world, entity_id, hex(9, -4), 20).is_some());
+  let mut counters = Counters::default();
+  for _ in 0..3 {
+    simulate_turn(&mut world, &mut counters);
+    assert_eq!(*world.position.get(entity_id).unwrap(), hex(9, -4));
+    assert_eq!(world.course(entity_id), Some(hex(0, 0)));
+  }
+}
+
+#[test]
+fn go_to_steers_around_asteroids() {
+  let mut world = World::new();
+  for r in -6..=6 {
+    asteroid(&mut world, hex(5, r));
+    asteroid(&mut world, hex(6, r));
+  }
+  let entity_id = ship_with_engine(&mut world, hex(0, 0), hex(0, 0), 1, 30);
+  world.combat.set(entity_id, CombatStrength { attack: 2, defence: 2 });
+  world.nav.set(entity_id, Nav::GoTo(hex(10, 0)));
+  assert!(turns_to_park(&mut world, entity_id, hex(10, 0), 30).is_some());
+}
+
+#[test]
+fn go_to_flies_through_gravity_without_crashing() {
+  let mut world = World::new();
+  spawn_planet(&mut world, "planet", hex(6, -1), 1, GravityStrength::Full, WHITE);
+  let entity_id = ship_with_engine(&mut world, hex(0, 0), hex(0, 0), 1, 30);
+  world.nav.set(entity_id, Nav::GoTo(hex(12, -2)));
+  assert!(turns_to_park(&mut world, entity_id, hex(12, -2), 30).is_some());
+}
+
+#[test]
+fn plan_course_gives_up_when_walled_in() {
+  let mut world = World::new();
+  spawn_planet(&mut world, "planet", hex(0, 0), 3, GravityStrength::Full, WHITE);
+  assert_eq!(plan_course(&world, hex(-10, 0), hex(0, 0), hex(0, 0), 1, 20), None);
+}
@@ -0,0 +1,80 @@
+use triplanetary::*;
+
+mod common;
+use common::*;
+
+// Records a few turns of a player ship thrusting, launching and firing at an
+// AI ship, giving orders the way the frontend does and keeping the ones the
+// simulation took. Returns the replay text and the final world.
+fn record_skirmish() -> (String, Box<World>) {
+  let (mut world, blue, red) = two_factions();
+  world.seed = 3;
+  world.vision_radius = 10;
+  let player = ai_ship(&mut world, "player", hex(0, 0), Difficulty::Normal);
+  world.ai.del(player);
+  world.owner.set(player, blue);
+  world.player = Some(player);
+  let enemy = ai_ship(&mut world, "enemy", hex(5, 0), Difficulty::Normal);
+  world.owner.set(enemy, red);
+  simulate_vision_system(&mut world);
+  let mut replay = Replay::record(&world);
+  let mut counters = Counters::default();
+  for direction in 0..4 {
+    let position = *world.position.get(player).unwrap();
+    let destination = position + world.course(player).unwrap() + Hex::direction(direction);
+    let orders = [
+      Order::Thrust(player, destination),
+      Order::Commit,
+      Order::Launch(player, OrdnanceKind::Torpedo, hex(1, 0)),
+      Order::Commit,
+      Order::Fire(player, enemy),
+      Order::Commit,
+    ];
+    for order in orders {
+      let turn = world.turn;
+      if simulate_order(&mut world, order) {
+        replay.push(turn, order);
+      }
+      simulate_step_system(&mut world, &mut counters);
+    }
+  }
+  assert!(replay.orders.iter().any(|x| matches!(x.order, Order::Launch(..))));
+  assert!(replay.orders.iter().any(|x| matches!(x.order, Order::Fire(..))));
+  (replay.to_save(&world).to_text(), world)
+}
+
+fn play_back(replay: Replay) -> (ReplayStatus, Box<World>) {
+  let (mut player, mut world) = ReplayPlayer::new(replay).unwrap();
+  let mut counters = Counters::default();
+  loop {
+    match player.step(&mut world, &mut counters) {
+      ReplayStatus::Playing => {},
+      status => return (status, world),
+    }
+  }
+}
+
+#[test]
+fn replays_play_back_to_the_recorded_world() {
+  let (text, world) = record_skirmish();
+  let save = SaveFile::parse(&text, SAVE_MIGRATIONS).unwrap();
+  assert!(Replay::is_replay(&save));
+  let (status, replayed) = play_back(Replay::from_save(save).unwrap());
+  assert!(matches!(status, ReplayStatus::Verified(x) if x == world.checksum()));
+  assert_eq!(replayed.turn, world.turn);
+}
+
+#[test]
+fn tampered_replays_diverge() {
+  let (text, _) = record_skirmish();
+  let save = SaveFile::parse(&text, SAVE_MIGRATIONS).unwrap();
+  let mut replay = Replay::from_save(save).unwrap();
+  let recorded = replay.orders.iter_mut()
+    .find(|x| matches!(x.order, Order::Thrust(..)))
+    .unwrap();
+  if let Order::Thrust(entity_id, destination) = recorded.order {
+    recorded.order = Order::Thrust(entity_id, destination + hex(-1, 0));
+  }
+  let (status, _) = play_back(replay);
+  assert!(matches!(status, ReplayStatus::Diverged { .. }));
+}
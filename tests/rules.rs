@@ -0,0 +1,214 @@
+use triplanetary::*;
+
+mod common;
+use common::*;
+
+// A planet at the origin pulling on the ring of hexes around it.
+fn planet_world() -> (Box<World>, EntityId) {
+  let mut world = World::new();
+  let planet = spawn_planet(&mut world, "planet", hex(0, 0), 0, GravityStrength::Full, WHITE);
+  (world, planet)
+}
+
+#[test]
+fn gravity_bends_the_move_after_the_one_that_entered_it() {
+  let (mut world, _) = planet_world();
+  let entity_id = ship_with_engine(&mut world, hex(-3, 1), hex(2, 0), 1, 20);
+  let mut counters = Counters::default();
+  simulate_turn(&mut world, &mut counters);
+  // the move ends in the well on a straight line
+  assert_eq!(world.position.get(entity_id), Some(&hex(-1, 1)));
+  assert_eq!(world.velocity.get(entity_id), Some(&hex(2, 0)));
+  assert_eq!(world.pending_gravity.get(entity_id), Some(&hex(1, -1)));
+  simulate_turn(&mut world, &mut counters);
+  assert_eq!(world.position.get(entity_id), Some(&hex(2, 0)));
+  assert_eq!(world.velocity.get(entity_id), Some(&hex(3, -1)));
+}
+
+#[test]
+fn the_final_hex_of_a_move_counts() {
+  let (mut world, _) = planet_world();
+  let entity_id = ship_with_engine(&mut world, hex(-2, 1), hex(1, 0), 1, 20);
+  simulate_turn(&mut world, &mut Counters::default());
+  assert_eq!(world.position.get(entity_id), Some(&hex(-1, 1)));
+  assert_eq!(world.course(entity_id), Some(hex(2, -1)));
+}
+
+#[test]
+fn wells_crossed_in_one_move_stack() {
+  let (mut world, _) = planet_world();
+  let entity_id = ship_with_engine(&mut world, hex(-3, 1), hex(2, 0), 1, 20);
+  let mut counters = Counters::default();
+  simulate_turn(&mut world, &mut counters);
+  // swinging round the planet through two of its gravity hexes
+  simulate_turn(&mut world, &mut counters);
+  assert_eq!(world.pending_gravity.get(entity_id), Some(&hex(-1, -1)));
+  simulate_turn(&mut world, &mut counters);
+  assert_eq!(world.position.get(entity_id), Some(&hex(4, -2)));
+  assert_eq!(world.velocity.get(entity_id), Some(&hex(2, -2)));
+}
+
+#[test]
+fn overlapping_wells_stack() {
+  let (mut world, _) = planet_world();
+  spawn_planet(&mut world, "moon", hex(1, 1), 0, GravityStrength::Full, WHITE);
+  let entity_id = ship_with_engine(&mut world, hex(2, -1), hex(-1, 1), 1, 20);
+  simulate_turn(&mut world, &mut Counters::default());
+  // both bodies pull on the hex between them
+  assert_eq!(world.position.get(entity_id), Some(&hex(1, 0)));
+  assert_eq!(world.pending_gravity.get(entity_id), Some(&hex(-1, 1)));
+}
+
+// A ship flying past a moon at the origin, asking to ignore weak gravity.
+fn ignoring_weak_gravity(position: Hex, velocity: Hex) -> (Box<World>, EntityId) {
+  let mut world = World::new();
+  spawn_planet(&mut world, "moon", hex(0, 0), 0, GravityStrength::Weak, WHITE);
+  let entity_id = ship_with_engine(&mut world, position, velocity, 1, 20);
+  assert!(simulate_order(&mut world, Order::IgnoreWeakGravity(entity_id, true)));
+  (world, entity_id)
+}
+
+#[test]
+fn a_single_weak_hex_can_be_ignored() {
+  let (mut world, entity_id) = ignoring_weak_gravity(hex(-2, 1), hex(1, 0));
+  simulate_turn(&mut world, &mut Counters::default());
+  assert_eq!(world.position.get(entity_id), Some(&hex(-1, 1)));
+  assert_eq!(world.course(entity_id), Some(hex(1, 0)));
+}
+
+#[test]
+fn two_weak_hexes_in_a_row_both_count() {
+  let (mut world, entity_id) = ignoring_weak_gravity(hex(-2, 1), hex(2, 0));
+  simulate_turn(&mut world, &mut Counters::default());
+  assert_eq!(world.position.get(entity_id), Some(&hex(0, 1)));
+  assert_eq!(world.pending_gravity.get(entity_id), Some(&hex(1, -2)));
+}
+
+#[test]
+fn a_weak_hex_stacked_with_a_full_well_counts() {
+  let (mut world, entity_id) = ignoring_weak_gravity(hex(2, -1), hex(-1, 1));
+  spawn_planet(&mut world, "planet", hex(1, 1), 0, GravityStrength::Full, WHITE);
+  simulate_turn(&mut world, &mut Counters::default());
+  assert_eq!(world.position.get(entity_id), Some(&hex(1, 0)));
+  assert_eq!(world.pending_gravity.get(entity_id), Some(&hex(-1, 1)));
+}
+
+#[test]
+fn circling_the_ring_at_speed_one_is_a_stable_orbit() {
+  let (mut world, planet) = planet_world();
+  let entity_id = ship_with_engine(&mut world, hex(-3, 1), hex(0, 0), 1, 20);
+  world.nav.set(entity_id, Nav::Orbit(planet));
+  let mut counters = Counters::default();
+  for _ in 0..10 {
+    simulate_turn(&mut world, &mut counters);
+  }
+  assert_eq!(world.orbit.get(entity_id), Some(&planet));
+  // once there it stays on without burning any more fuel
+  world.nav.del(entity_id);
+  let fuel = world.engine.get(entity_id).unwrap().fuel;
+  for _ in 0..6 {
+    simulate_turn(&mut world, &mut counters);
+    assert_eq!(world.orbit.get(entity_id), Some(&planet));
+    assert_eq!(world.position.get(entity_id).unwrap().mag(), 1);
+  }
+  assert_eq!(world.engine.get(entity_id).unwrap().fuel, fuel);
+}
+
+#[test]
+fn ships_land_by_dropping_out_of_orbit() {
+  let (mut world, planet) = planet_world();
+  let entity_id = ship_with_engine(&mut world, hex(-3, 1), hex(0, 0), 1, 20);
+  world.nav.set(entity_id, Nav::Land(planet));
+  let mut counters = Counters::default();
+  for _ in 0..14 {
+    simulate_turn(&mut world, &mut counters);
+  }
+  assert_eq!(world.landed.get(entity_id), Some(&planet));
+  assert_eq!(world.velocity.get(entity_id), Some(&hex(0, 0)));
+  // landed ships rest on the surface, out of the pull of the planet
+  let position = *world.position.get(entity_id).unwrap();
+  world.nav.del(entity_id);
+  simulate_turn(&mut world, &mut counters);
+  assert_eq!(world.position.get(entity_id), Some(&position));
+  assert_eq!(world.course(entity_id), Some(hex(0, 0)));
+}
+
+#[test]
+fn takeoff_boost_needs_no_fuel() {
+  let (mut world, planet) = planet_world();
+  let entity_id = ship_with_engine(&mut world, hex(-1, 1), hex(0, 0), 1, 0);
+  world.landed.set(entity_id, planet);
+  assert_eq!(world.thrust_limit(entity_id), Some(TAKEOFF_BOOST));
+  world.plot.set(entity_id, hex(-1, 1));
+  simulate_turn(&mut world, &mut Counters::default());
+  assert_eq!(world.position.get(entity_id), Some(&hex(-2, 2)));
+  assert!(!world.landed.has(entity_id));
+  assert_eq!(world.engine.get(entity_id).unwrap().fuel, 0);
+}
+
+// Where a ship ended up, how fast and for how long it is disabled.
+type Outcome = (Option<Hex>, Option<Hex>, i32);
+
+// Two ships closing on the same hex from the west and the south at the same
+// speed, reaching it in the same sub-step. Returns what happened to each with
+// the ids swapped for which way it came from.
+fn converge(west_first: bool, speed: i32) -> (Vec<Outcome>, Vec<String>) {
+  let mut world = World::new();
+  let mut ids = [ship(&mut world, hex(0, 0)), ship(&mut world, hex(0, 0))];
+  if !west_first {
+    ids.reverse();
+  }
+  let (west, south) = (ids[0], ids[1]);
+  world.name.set(west, "west".to_string());
+  world.name.set(south, "south".to_string());
+  world.position.set(west, hex(-speed, 0));
+  world.velocity.set(west, hex(speed, 0));
+  world.position.set(south, hex(0, speed));
+  world.velocity.set(south, hex(0, -speed));
+  simulate_turn(&mut world, &mut Counters::default());
+  let outcome = [west, south].iter()
+    .map(|x| (world.position.get(*x).copied(), world.velocity.get(*x).copied(), world.disabled_turns(*x)))
+    .collect();
+  let events = world.events.iter()
+    .map(|x| format!("{:?}", x)
+      .replace(&format!("{:?}", west), "west")
+      .replace(&format!("{:?}", south), "south"))
+    .collect();
+  (outcome, events)
+}
+
+#[test]
+fn same_hex_arrivals_do_not_depend_on_entity_ids() {
+  for speed in [2, 3, 4] {
+    let (outcome, events) = converge(true, speed);
+    assert!(events.iter().any(|x| x.starts_with("Rammed")));
+    assert_eq!(converge(false, speed), (outcome, events));
+  }
+}
+
+#[test]
+fn launches_beyond_the_class_limit_are_refused() {
+  let mut world = World::new();
+  let entity_id = ai_ship(&mut world, "a", hex(0, 0), Difficulty::Normal);
+  world.ai.del(entity_id);
+  world.phase = Phase::Ordnance;
+  let class = ship_class("corvette").unwrap();
+  assert_eq!(class.launches_per_turn, 1);
+  assert!(simulate_order(&mut world, Order::Launch(entity_id, OrdnanceKind::Torpedo, hex(0, 0))));
+  assert!(!simulate_order(&mut world, Order::Launch(entity_id, OrdnanceKind::Mine, hex(0, 0))));
+  // corvettes carry no nukes at all
+  world.launch.del(entity_id);
+  assert!(!simulate_order(&mut world, Order::Launch(entity_id, OrdnanceKind::Nuke, hex(0, 0))));
+}
+
+#[test]
+fn ship_classes_are_read_from_data() {
+  let classes = parse_ship_classes("# comment\n\nscout 2 Mine 1 Torpedo 3\n").unwrap();
+  assert_eq!(classes.len(), 1);
+  assert_eq!(classes[0].name, "scout");
+  assert_eq!(classes[0].launches_per_turn, 2);
+  assert_eq!(classes[0].ordnance, vec![(OrdnanceKind::Mine, 1), (OrdnanceKind::Torpedo, 3)]);
+  assert!(parse_ship_classes("scout 2 Mine").is_err());
+  assert!(parse_ship_classes("scout 2 Laser 1").is_err());
+  assert!(ship_classes().iter().any(|x| x.name == "dreadnaught"));
+}
@@ -0,0 +1,47 @@
+use triplanetary::*;
+
+mod common;
+use common::*;
+
+// A small game with a planet to fall around, a station keeping a patch of
+// generated terrain loaded and two AI ships closing on each other. The
+// fixture save is this world before its first turn.
+fn skirmish() -> Box<World> {
+  let (mut world, blue, red) = two_factions();
+  world.seed = 7;
+  world.vision_radius = 8;
+  spawn_planet(&mut world, "planet", hex(0, -4), 0, GravityStrength::Full, GRAY);
+  let station = spawn_base(&mut world, "station", hex(0, 6), YELLOW);
+  world.anchor.set(station, 4);
+  let a = ai_ship(&mut world, "a", hex(-6, 0), Difficulty::Normal);
+  world.owner.set(a, blue);
+  let b = ai_ship(&mut world, "b", hex(6, 0), Difficulty::Normal);
+  world.owner.set(b, red);
+  simulate_generate_around_anchors_system(&mut world);
+  simulate_vision_system(&mut world);
+  world
+}
+
+#[test]
+fn fixture_save_plays_on_like_the_live_game() {
+  let save = SaveFile::parse(include_str!("fixtures/skirmish.save"), SAVE_MIGRATIONS).unwrap();
+  let mut loaded = World::load(&save).unwrap();
+  let mut live = skirmish();
+  assert!(live.generated.iter().next().is_some());
+  assert_eq!(loaded.checksum(), live.checksum());
+  let mut counters = Counters::default();
+  for _ in 0..10 {
+    simulate_turn(&mut loaded, &mut counters);
+    simulate_turn(&mut live, &mut counters);
+    assert_eq!(loaded.checksum(), live.checksum());
+  }
+  assert_eq!(loaded.turn, 11);
+}
+
+// Writes the fixture again after the world above changes, with
+// `cargo test --test save -- --ignored`.
+#[test]
+#[ignore]
+fn write_fixture() {
+  std::fs::write("tests/fixtures/skirmish.save", skirmish().save().to_text()).unwrap();
+}
@@ -0,0 +1,143 @@
+use std::collections::BTreeMap;
+use triplanetary::*;
+
+mod common;
+use common::*;
+
+fn sensor_ship(world: &mut World, position: Hex, faction_id: FactionId, sensor: Sensor) -> EntityId {
+  let entity_id = owned_ship(world, position, faction_id);
+  world.sensor.set(entity_id, sensor);
+  entity_id
+}
+
+fn passive(range: i32) -> Sensor {
+  Sensor { range, active: false }
+}
+
+fn contact(world: &World, faction_id: FactionId, entity_id: EntityId) -> Option<Contact> {
+  world.factions[&faction_id].contacts.get(&entity_id).copied()
+}
+
+#[test]
+fn distant_ships_are_blips_until_identified() {
+  let (mut world, blue, red) = two_factions();
+  sensor_ship(&mut world, hex(0, 0), blue, passive(10));
+  let target = sensor_ship(&mut world, hex(8, 0), red, passive(10));
+  simulate_vision_system(&mut world);
+  assert_eq!(contact(&world, blue, target), Some(Contact { position: hex(8, 0), identified: false }));
+  assert!(!world.factions[&blue].visible.contains(&hex(8, 0)));
+  world.position.set(target, hex(4, 0));
+  simulate_vision_system(&mut world);
+  assert_eq!(contact(&world, blue, target), Some(Contact { position: hex(4, 0), identified: true }));
+}
+
+#[test]
+fn thrusting_ships_are_detected_further_out() {
+  let (mut world, blue, red) = two_factions();
+  sensor_ship(&mut world, hex(0, 0), blue, passive(6));
+  let target = sensor_ship(&mut world, hex(10, 0), red, passive(6));
+  simulate_vision_system(&mut world);
+  assert_eq!(contact(&world, blue, target), None);
+  // the burn is still showing after the move it was made for
+  world.plot.set(target, hex(1, 0));
+  simulate_burn_system(&mut world);
+  simulate_movement_system(&mut world);
+  simulate_vision_system(&mut world);
+  assert_eq!(contact(&world, blue, target).map(|x| x.identified), Some(false));
+  // coasting the next turn it fades out again
+  simulate_burn_system(&mut world);
+  simulate_movement_system(&mut world);
+  simulate_vision_system(&mut world);
+  assert_eq!(contact(&world, blue, target), None);
+}
+
+#[test]
+fn active_sensors_give_the_ship_away() {
+  let (mut world, blue, red) = two_factions();
+  let scout = sensor_ship(&mut world, hex(0, 0), blue, Sensor { range: 10, active: true });
+  sensor_ship(&mut world, hex(15, 0), red, passive(10));
+  simulate_vision_system(&mut world);
+  assert!(world.factions[&blue].contacts.is_empty());
+  assert_eq!(contact(&world, red, scout).map(|x| x.identified), Some(false));
+  assert!(simulate_order(&mut world, Order::Sensors(scout, false)));
+  simulate_vision_system(&mut world);
+  assert_eq!(contact(&world, red, scout), None);
+}
+
+#[test]
+fn active_sensors_identify_at_full_range() {
+  let (mut world, blue, red) = two_factions();
+  let scout = sensor_ship(&mut world, hex(0, 0), blue, passive(10));
+  let target = sensor_ship(&mut world, hex(8, 0), red, passive(10));
+  simulate_vision_system(&mut world);
+  assert_eq!(contact(&world, blue, target).map(|x| x.identified), Some(false));
+  assert!(simulate_order(&mut world, Order::Sensors(scout, true)));
+  simulate_vision_system(&mut world);
+  assert_eq!(contact(&world, blue, target).map(|x| x.identified), Some(true));
+}
+
+#[test]
+fn contacts_stay_identified_while_tracked() {
+  let (mut world, blue, red) = two_factions();
+  sensor_ship(&mut world, hex(0, 0), blue, passive(10));
+  let target = sensor_ship(&mut world, hex(4, 0), red, passive(10));
+  simulate_vision_system(&mut world);
+  world.position.set(target, hex(9, 0));
+  simulate_vision_system(&mut world);
+  assert_eq!(contact(&world, blue, target), Some(Contact { position: hex(9, 0), identified: true }));
+  let loaded = World::load(&world.save()).unwrap();
+  assert_eq!(contact(&loaded, blue, target), contact(&world, blue, target));
+  assert_eq!(loaded.checksum(), world.checksum());
+  // once lost, it has to be identified all over again
+  world.position.set(target, hex(30, 0));
+  simulate_vision_system(&mut world);
+  assert_eq!(contact(&world, blue, target), None);
+  world.position.set(target, hex(9, 0));
+  simulate_vision_system(&mut world);
+  assert_eq!(contact(&world, blue, target).map(|x| x.identified), Some(false));
+}
+
+#[test]
+fn ai_ignores_unidentified_blips() {
+  let (mut world, blue, red) = two_factions();
+  let hunter = sensor_ship(&mut world, hex(0, 0), blue, passive(10));
+  world.ai.set(hunter, Ai::new(Difficulty::Normal));
+  let target = sensor_ship(&mut world, hex(8, 0), red, passive(10));
+  simulate_vision_system(&mut world);
+  assert_eq!(best_intent(&world, hunter), Intent::Idle);
+  world.position.set(target, hex(5, 0));
+  simulate_vision_system(&mut world);
+  assert_ne!(best_intent(&world, hunter), Intent::Idle);
+}
+
+#[test]
+fn saves_from_before_contacts_still_load() {
+  let (mut world, blue, red) = two_factions();
+  sensor_ship(&mut world, hex(0, 0), blue, passive(10));
+  let mut save = world.save();
+  type Old = ((String, Color), (Vec<FactionId>, Vec<(i32, i32)>));
+  let old: BTreeMap<FactionId, Old> = vec![
+    (blue, (("blue".to_string(), BLUE), (vec![], vec![(0, 0), (1, 0)]))),
+    (red, (("red".to_string(), RED), (vec![], vec![]))),
+  ].into_iter().collect();
+  save.put("factions", &old);
+  save.version = 2;
+  let save = SaveFile::parse(&save.to_text(), SAVE_MIGRATIONS).unwrap();
+  let loaded = World::load(&save).unwrap();
+  assert!(loaded.factions[&blue].contacts.is_empty());
+  assert!(loaded.factions[&blue].explored.contains_key(&hex(1, 0)));
+  assert_eq!(loaded.factions[&red].name, "red");
+}
+
+#[test]
+fn only_identified_targets_can_be_fired_on() {
+  let (mut world, blue, red) = two_factions();
+  let gunner = sensor_ship(&mut world, hex(0, 0), blue, passive(10));
+  let target = sensor_ship(&mut world, hex(30, 0), red, passive(10));
+  world.phase = Phase::Combat;
+  for (position, allowed) in [(hex(30, 0), false), (hex(8, 0), false), (hex(4, 0), true)] {
+    world.position.set(target, position);
+    simulate_vision_system(&mut world);
+    assert_eq!(simulate_order(&mut world, Order::Fire(gunner, target)), allowed);
+  }
+}
@@ -0,0 +1,63 @@
+use triplanetary::*;
+
+// Commits every phase the way the frontend does, stepping the simulation
+// once per frame, until the next astrogation.
+fn commit_turn(world: &mut World, counters: &mut Counters) {
+  loop {
+    assert!(simulate_order(world, Order::Commit));
+    simulate_step_system(world, counters);
+    if world.phase == Phase::Astrogation {
+      break;
+    }
+  }
+}
+
+#[test]
+fn scenario_plays_without_a_window() {
+  let mut world = World::new();
+  initialize(&mut world, 12);
+  let mut counters = Counters::default();
+  let player = world.player.unwrap();
+  // out and back again, so the ship ends up at rest where it started
+  for (turn, direction) in [(1, 3), (2, 0), (3, 0), (4, 3)] {
+    assert_eq!(world.turn, turn);
+    let position = *world.position.get(player).unwrap();
+    let destination = position + world.course(player).unwrap() + Hex::direction(direction);
+    assert!(simulate_order(&mut world, Order::Thrust(player, destination)));
+    commit_turn(&mut world, &mut counters);
+    assert_eq!(world.position.get(player), Some(&destination));
+  }
+  assert_eq!(world.position.get(player), Some(&hex(0, 0)));
+  assert_eq!(world.velocity.get(player), Some(&hex(0, 0)));
+}
+
+#[test]
+fn ships_coast_through_turns_without_orders() {
+  let mut world = World::new();
+  initialize(&mut world, 12);
+  let player = world.player.unwrap();
+  world.velocity.set(player, hex(-1, 0));
+  let mut counters = Counters::default();
+  for _ in 0..3 {
+    simulate_turn(&mut world, &mut counters);
+  }
+  assert_eq!(world.turn, 4);
+  assert_eq!(world.phase, Phase::Astrogation);
+  assert_eq!(world.position.get(player), Some(&hex(-3, 0)));
+}
+
+#[test]
+fn same_seed_plays_the_same_game() {
+  let play = || {
+    let mut world = World::new();
+    initialize(&mut world, 12);
+    let mut counters = Counters::default();
+    let mut checksums = vec![];
+    for _ in 0..10 {
+      simulate_turn(&mut world, &mut counters);
+      checksums.push(world.checksum());
+    }
+    checksums
+  };
+  assert_eq!(play(), play());
+}